@@ -0,0 +1,358 @@
+use crate::json_ld::{JsonLd, PostalAddress};
+use lazy_static::lazy_static;
+use pyo3::prelude::*;
+use regex::Regex;
+use serde_json::Value;
+
+lazy_static! {
+    // "8230 Åbyhøj", "DK-8230 Åbyhøj", "114 55 Stockholm", "1012 AB Amsterdam", "00-950 Warszawa"
+    static ref POSTCODE_CITY_RE: Regex = Regex::new(
+        r"^(?:(?P<prefix>[A-Z]{1,2})[- ])?(?P<postcode>\d{4}-\d{3}|\d{2}-\d{3}|\d{3} \d{2}|\d{4} ?[A-Z]{2}|\d{3,5})\s+(?P<city>\p{Lu}[\p{L}.'\- ]*?(?: [A-Z])?)$"
+    )
+    .expect("Invalid Regex");
+    // "London SW1A 1AA", "SW1A 1AA", "Dublin 2 D02 X285"
+    static ref UK_POSTCODE_RE: Regex = Regex::new(
+        r"^(?:(?P<city>\p{Lu}[\p{L}\d.'\- ]*?),?\s+)?(?P<postcode>[A-Z]{1,2}\d[A-Z\d]? ?\d[A-Z]{2}|[A-Z]\d[\dW] ?[A-Z\d]{4})$"
+    )
+    .expect("Invalid Regex");
+    // "Søren Frichs Vej 36 F", "Hauptstraße 5a", "12 rue de la Paix", "10 Downing Street"
+    static ref STREET_RE: Regex = Regex::new(
+        r"^(?:\p{L}[\p{L}.'\- ]*\s\d+\s?[A-Za-z]?(?:[,\s\-/].{0,20})?|\d+[A-Za-z]?,?\s\p{L}[\p{L}.'\- ]+)$"
+    )
+    .expect("Invalid Regex");
+    // Common street types, the tail of compound names like "Frichsvej" included
+    static ref STREET_SUFFIX_RE: Regex = Regex::new(
+        r"(?i)(vej|gade|plads|allé|alle|torv|stræde|straße|strasse|str\.|weg|platz|gatan|gata|vägen|väg|veien|vei|gate|katu|tie|straat|laan|plein|rue|avenue|boulevard|road|street|lane|via|viale|piazza|calle|avenida|ulica|ul\.)\b"
+    )
+    .expect("Invalid Regex");
+    static ref POSTCODE_FORMAT_RES: Vec<(&'static str, Regex)> = POSTCODE_FORMATS
+        .iter()
+        .map(|(country, format)| (*country, Regex::new(format).expect("Invalid Regex")))
+        .collect();
+}
+
+const MIN_TEXT_CONFIDENCE: f64 = 0.3;
+
+// Postcode format per country, checked against a country hint
+const POSTCODE_FORMATS: [(&str, &str); 24] = [
+    ("DK", r"^\d{4}$"),
+    ("NO", r"^\d{4}$"),
+    ("AT", r"^\d{4}$"),
+    ("CH", r"^\d{4}$"),
+    ("BE", r"^\d{4}$"),
+    ("LU", r"^\d{4}$"),
+    ("HU", r"^\d{4}$"),
+    ("DE", r"^\d{5}$"),
+    ("FI", r"^\d{5}$"),
+    ("FR", r"^\d{5}$"),
+    ("ES", r"^\d{5}$"),
+    ("IT", r"^\d{5}$"),
+    ("EE", r"^\d{5}$"),
+    ("HR", r"^\d{5}$"),
+    ("SE", r"^\d{3} ?\d{2}$"),
+    ("CZ", r"^\d{3} ?\d{2}$"),
+    ("SK", r"^\d{3} ?\d{2}$"),
+    ("GR", r"^\d{3} ?\d{2}$"),
+    ("NL", r"^\d{4} ?[A-Z]{2}$"),
+    ("PL", r"^\d{2}-\d{3}$"),
+    ("PT", r"^\d{4}-\d{3}$"),
+    ("IS", r"^\d{3}$"),
+    ("GB", r"^[A-Z]{1,2}\d[A-Z\d]? ?\d[A-Z]{2}$"),
+    ("IE", r"^[A-Z]\d[\dW] ?[A-Z\d]{4}$"),
+];
+
+const COUNTRY_NAMES: [(&str, &str); 36] = [
+    ("danmark", "DK"),
+    ("denmark", "DK"),
+    ("sverige", "SE"),
+    ("sweden", "SE"),
+    ("norge", "NO"),
+    ("norway", "NO"),
+    ("suomi", "FI"),
+    ("finland", "FI"),
+    ("deutschland", "DE"),
+    ("germany", "DE"),
+    ("österreich", "AT"),
+    ("austria", "AT"),
+    ("schweiz", "CH"),
+    ("suisse", "CH"),
+    ("switzerland", "CH"),
+    ("nederland", "NL"),
+    ("netherlands", "NL"),
+    ("the netherlands", "NL"),
+    ("belgië", "BE"),
+    ("belgique", "BE"),
+    ("belgium", "BE"),
+    ("france", "FR"),
+    ("españa", "ES"),
+    ("spain", "ES"),
+    ("italia", "IT"),
+    ("italy", "IT"),
+    ("polska", "PL"),
+    ("poland", "PL"),
+    ("portugal", "PT"),
+    ("united kingdom", "GB"),
+    ("uk", "GB"),
+    ("england", "GB"),
+    ("ireland", "IE"),
+    ("ísland", "IS"),
+    ("iceland", "IS"),
+    ("luxembourg", "LU"),
+];
+
+#[pyclass(get_all, module = "html_parsing_tools")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct AddressCandidate {
+    pub street: Option<String>,
+    pub postcode: Option<String>,
+    pub city: Option<String>,
+    /// ISO 3166 alpha-2
+    pub country: Option<String>,
+    /// 0..1; structured data scores highest, a lone "postcode city" line lowest
+    pub confidence: f64,
+    /// "json_ld", "microdata", "rdfa" or "text"
+    pub source: String,
+    /// the lines the candidate was read from
+    pub lines: Vec<String>,
+}
+
+fn country_by_name(name: &str) -> Option<String> {
+    let name = name.trim().trim_end_matches('.').to_lowercase();
+    COUNTRY_NAMES
+        .iter()
+        .find(|(country_name, _)| *country_name == name)
+        .map(|(_, code)| code.to_string())
+}
+
+// A country name, or failing that an ISO alpha-2 code
+fn country_from_name(name: &str) -> Option<String> {
+    country_by_name(name).or_else(|| {
+        let code = name.trim().trim_end_matches('.');
+        if code.chars().count() != 2 {
+            return None;
+        }
+        rust_iso3166::from_alpha2(&code.to_uppercase()).map(|c| c.alpha2.to_string())
+    })
+}
+
+fn postcode_matches(country: &str, postcode: &str) -> bool {
+    POSTCODE_FORMAT_RES
+        .iter()
+        .filter(|(code, _)| *code == country)
+        .any(|(_, format)| format.is_match(postcode))
+}
+
+fn is_street(line: &str) -> bool {
+    STREET_RE.is_match(line) && line.split_whitespace().count() <= 6
+}
+
+// "Søren Frichs Vej 36 F, 8230 Åbyhøj" and a block of lines are both read line by line
+fn split_lines(group: &[String]) -> Vec<String> {
+    group
+        .iter()
+        .flat_map(|line| line.split([',', '|', '·', '•']))
+        .map(|line| line.split_whitespace().collect::<Vec<&str>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+// Candidates from `group_text_nodes` output; each group is one block of nearby text
+pub fn find_text_addresses(
+    text_nodes: &[Vec<String>],
+    country_hint: Option<&str>,
+) -> Vec<AddressCandidate> {
+    let mut result: Vec<AddressCandidate> = vec![];
+    for group in text_nodes {
+        let lines = split_lines(group);
+        for (ix, line) in lines.iter().enumerate() {
+            let (prefix, postcode, city) = if let Some(captures) = POSTCODE_CITY_RE.captures(line) {
+                (
+                    captures.name("prefix").map(|m| m.as_str().to_string()),
+                    captures["postcode"].to_string(),
+                    Some(captures["city"].trim().to_string()),
+                )
+            } else if let Some(captures) = UK_POSTCODE_RE.captures(line) {
+                (
+                    None,
+                    captures["postcode"].to_string(),
+                    captures.name("city").map(|m| m.as_str().trim().to_string()),
+                )
+            } else {
+                continue;
+            };
+            if city.as_ref().is_some_and(|city| city.split_whitespace().count() > 4) {
+                continue;
+            }
+
+            let mut first = ix;
+            let mut street = None;
+            let mut city = city;
+            if ix >= 1 && is_street(&lines[ix - 1]) && !POSTCODE_CITY_RE.is_match(&lines[ix - 1]) {
+                first = ix - 1;
+                street = Some(lines[ix - 1].clone());
+            } else if city.is_none() && ix >= 2 && is_street(&lines[ix - 2]) {
+                // UK style puts the town on its own line between street and postcode
+                first = ix - 2;
+                street = Some(lines[ix - 2].clone());
+                city = Some(lines[ix - 1].clone());
+            }
+            // any two letters read as a code, so a bare code needs the postcode to agree
+            let stated_country = lines.get(ix + 1).and_then(|next| {
+                country_by_name(next).or_else(|| {
+                    country_from_name(next).filter(|country| postcode_matches(country, &postcode))
+                })
+            });
+            let prefix_country = prefix.as_deref().and_then(country_from_name);
+            // "2024 Annual Report" and "500 Employees" read as a lone postcode and city,
+            // so those need a street, a country or letters in the postcode to count
+            let has_letters = postcode.chars().any(|c| c.is_ascii_alphabetic());
+            if street.is_none() && stated_country.is_none() && prefix_country.is_none() && !has_letters
+            {
+                continue;
+            }
+
+            // only the UK and Ireland write letters in the postcode itself
+            let format_country = ["GB", "IE"]
+                .into_iter()
+                .find(|country| postcode_matches(country, &postcode))
+                .map(|country| country.to_string());
+            let country = stated_country
+                .clone()
+                .or(prefix_country.clone())
+                .or(format_country)
+                .or_else(|| country_hint.map(|c| c.to_uppercase()));
+            let mut confidence: f64 = 0.4;
+            if let Some(street) = &street {
+                confidence += 0.2;
+                if STREET_SUFFIX_RE.is_match(street) {
+                    confidence += 0.1;
+                }
+            }
+            if country.as_deref().is_some_and(|c| postcode_matches(c, &postcode)) {
+                confidence += 0.1;
+            } else if country.is_some() {
+                confidence -= 0.2;
+            }
+            if stated_country.is_some() || prefix_country.is_some() {
+                confidence += 0.1;
+            }
+            // a postcode in the wrong format for the country
+            if confidence < MIN_TEXT_CONFIDENCE {
+                continue;
+            }
+
+            result.push(AddressCandidate {
+                street,
+                postcode: Some(postcode),
+                city,
+                country,
+                confidence: (confidence * 100.0).round() / 100.0,
+                source: "text".to_string(),
+                lines: lines[first..=ix].to_vec(),
+            });
+        }
+    }
+    result
+}
+
+fn from_postal_address(address: &PostalAddress, source: &str, confidence: f64) -> AddressCandidate {
+    AddressCandidate {
+        street: address.street_address.clone(),
+        postcode: address.postal_code.clone(),
+        city: address.locality.clone(),
+        country: address.country.as_deref().and_then(country_from_name),
+        confidence,
+        source: source.to_string(),
+        lines: vec![],
+    }
+}
+
+// Addresses from JSON-LD, including ones only given as a single string
+pub fn find_json_ld_addresses(
+    json_ld: &JsonLd,
+    country_hint: Option<&str>,
+) -> Vec<AddressCandidate> {
+    let addresses = json_ld
+        .organizations
+        .iter()
+        .filter_map(|organization| organization.address.as_ref())
+        .chain(json_ld.local_businesses.iter().filter_map(|business| business.address.as_ref()))
+        .chain(json_ld.addresses.iter());
+
+    let mut result: Vec<AddressCandidate> = vec![];
+    for address in addresses {
+        let unparsed = address.postal_code.is_none() && address.locality.is_none();
+        match (&address.street_address, unparsed) {
+            (Some(text), true) => {
+                for mut candidate in find_text_addresses(&[vec![text.clone()]], country_hint) {
+                    candidate.source = "json_ld".to_string();
+                    candidate.confidence = (candidate.confidence + 0.1).min(0.9);
+                    result.push(candidate);
+                }
+            }
+            _ => result.push(from_postal_address(address, "json_ld", 0.95)),
+        }
+    }
+    result
+}
+
+// PostalAddress items anywhere in microdata or RDFa item trees
+pub fn find_item_addresses(items: &[Value], source: &str) -> Vec<AddressCandidate> {
+    let mut result: Vec<AddressCandidate> = vec![];
+    let mut pending: Vec<&Value> = items.iter().collect();
+    while let Some(item) = pending.pop() {
+        let is_address = item["type"]
+            .as_array()
+            .is_some_and(|types| {
+                types.iter().filter_map(Value::as_str).any(|t| t.ends_with("PostalAddress"))
+            });
+        let properties = &item["properties"];
+        let property = |name: &str| -> Option<String> {
+            properties
+                .as_object()?
+                .iter()
+                .find(|(key, _)| key.rsplit([':', '/']).next() == Some(name))?
+                .1
+                .as_array()?
+                .iter()
+                .find_map(Value::as_str)
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        if is_address {
+            let address = PostalAddress {
+                street_address: property("streetAddress"),
+                postal_code: property("postalCode"),
+                locality: property("addressLocality"),
+                region: property("addressRegion"),
+                country: property("addressCountry"),
+                post_office_box: property("postOfficeBoxNumber"),
+            };
+            result.push(from_postal_address(&address, source, 0.9));
+        }
+        if let Some(properties) = properties.as_object() {
+            for values in properties.values().filter_map(Value::as_array) {
+                pending.extend(values.iter().filter(|value| value.is_object()));
+            }
+        }
+    }
+    result
+}
+
+// Highest confidence first; the same street and postcode only once
+pub fn merge_addresses(mut candidates: Vec<AddressCandidate>) -> Vec<AddressCandidate> {
+    candidates.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    let mut result: Vec<AddressCandidate> = vec![];
+    for candidate in candidates {
+        let key = |c: &AddressCandidate| {
+            (
+                c.street.as_ref().map(|s| s.to_lowercase()),
+                c.postcode.as_ref().map(|p| p.replace(' ', "")),
+            )
+        };
+        if !result.iter().any(|existing| key(existing) == key(&candidate)) {
+            result.push(candidate);
+        }
+    }
+    result
+}
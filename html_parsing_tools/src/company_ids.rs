@@ -0,0 +1,514 @@
+use kuchiki::iter::NodeIterator;
+use kuchiki::NodeRef;
+use lazy_static::lazy_static;
+use pyo3::prelude::*;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+
+#[pyclass(get_all, module = "html_parsing_tools")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompanyId {
+    /// "vat", "cvr", "org_nr", "y_tunnus", "kvk", "company_number", "hra" or "hrb"
+    pub kind: String,
+    /// digits and letters only, VAT numbers with their country prefix
+    pub value: String,
+    /// the identifier as written on the page
+    pub original: String,
+    /// ISO 3166 alpha-2; Greek VAT numbers use "GR" although their prefix is "EL"
+    pub country: String,
+    /// the registry court of German HRA/HRB numbers, when given
+    pub registry: Option<String>,
+    /// true when the number passed its scheme's checksum
+    pub validated: bool,
+}
+
+struct Register {
+    kind: &'static str,
+    country: &'static str,
+    re: Regex,
+    check: Option<fn(&str) -> bool>,
+}
+
+fn register(
+    kind: &'static str,
+    country: &'static str,
+    re: &str,
+    check: Option<fn(&str) -> bool>,
+) -> Register {
+    Register {
+        kind,
+        country,
+        re: Regex::new(re).expect("Invalid Regex"),
+        check,
+    }
+}
+
+lazy_static! {
+    // National registers, recognized by their label
+    static ref REGISTERS: Vec<Register> = vec![
+        register(
+            "cvr",
+            "DK",
+            r"(?i)\bcvr(?:[\s.\-]*(?:nr|nummer|no))?\.?\s*[:#]?\s*(?:DK[\s\-]?)?(?P<number>\d{2}[\s.]?\d{2}[\s.]?\d{2}[\s.]?\d{2})\b",
+            Some(dk_check),
+        ),
+        register(
+            "org_nr",
+            "SE",
+            r"(?i)\borg(?:anisations)?[\s.\-]*(?:nr|nummer)\.?\s*[:#]?\s*(?P<number>\d{6}-?\d{4})\b",
+            Some(luhn_check),
+        ),
+        register(
+            "org_nr",
+            "NO",
+            r"(?i)\borg(?:anisasjons)?[\s.\-]*(?:nr|nummer)\.?\s*[:#]?\s*(?:NO\s?)?(?P<number>\d{3}\s?\d{3}\s?\d{3})\b",
+            Some(no_check),
+        ),
+        register(
+            "y_tunnus",
+            "FI",
+            r"(?i)\b(?:y-tunnus|fo-nummer|business\s+id)\s*[:#]?\s*(?P<number>\d{7}-\d)\b",
+            Some(fi_check),
+        ),
+        register(
+            "kvk",
+            "NL",
+            r"(?i)\b(?:kvk|kamer\s+van\s+koophandel)(?:[\s.\-]*(?:nr|nummer))?\.?\s*[:#]?\s*(?P<number>\d{8})\b",
+            None,
+        ),
+        register(
+            "company_number",
+            "GB",
+            r"(?i)\b(?:company(?:\s+registration)?|registered\s+in\s+(?:england|scotland|wales|northern\s+ireland)(?:\s+(?:and|&)\s+wales)?[,.]?(?:\s+with)?(?:\s+company)?|registration)\s+(?:no|number|nr)\.?\s*[:#]?\s*(?P<number>(?:SC|NI|OC|SO|NC|R0|IP|SP|NF|FC|LP|SL|NL|GE)?\d{6,8})\b",
+            None,
+        ),
+    ];
+    // "Amtsgericht München HRB 12345", "Registergericht: Amtsgericht Köln, HRB 4711"
+    static ref HANDELSREGISTER_RE: Regex = Regex::new(
+        r"(?:(?:Amtsgericht|Registergericht|AG)\s*:?\s*(?:Amtsgericht\s+)?(?P<court>\p{Lu}[\p{L}\-]+(?:\s(?:am|an\sder|in\sder|i\.)?\s?\p{Lu}[\p{L}\-.]+)?)[,\s]+)?(?:Handelsregister(?:nummer)?\s*:?\s*)?\b(?P<kind>HR[AB])\s*:?\s*(?P<number>\d{1,6}(?:\s?[A-Z]{1,2}\b)?)"
+    )
+    .expect("Invalid Regex");
+    // "DK13394172", "DE 123 456 789", "CHE-123.456.788 MWST"; trimmed to the country format later
+    static ref VAT_PREFIXED_RE: Regex = Regex::new(
+        r"\b(?P<prefix>AT|BE|BG|CY|CZ|DE|DK|EE|EL|ES|FI|FR|GB|XI|HR|HU|IE|IT|LT|LU|LV|MT|NL|PL|PT|RO|SE|SI|SK|NO|CH)[\s\-]?(?P<number>[0-9A-Z][0-9A-Z\s.\-*+]{5,20})"
+    )
+    .expect("Invalid Regex");
+    // a VAT label followed by a number without its country prefix
+    static ref VAT_LABEL_RE: Regex = Regex::new(
+        r"(?i)\b(?P<label>moms|mva|mwst|ust|btw|tva|iva|nip|alv|vat)\b[^\d\n]{0,30}?(?P<number>\d[\d\s.\-]{6,14}\d)\b"
+    )
+    .expect("Invalid Regex");
+    static ref VAT_FORMAT_RES: HashMap<&'static str, Regex> = VAT_FORMATS
+        .iter()
+        .chain([("CH", CH_VAT_FORMAT)].iter())
+        .map(|(prefix, format)| (*prefix, Regex::new(format).expect("Invalid Regex")))
+        .collect();
+}
+
+// VAT number format after the prefix, with separators removed
+const VAT_FORMATS: [(&str, &str); 30] = [
+    ("AT", r"^U\d{8}$"),
+    ("BE", r"^[01]\d{9}$"),
+    ("BG", r"^\d{9,10}$"),
+    ("CY", r"^\d{8}[A-Z]$"),
+    ("CZ", r"^\d{8,10}$"),
+    ("DE", r"^\d{9}$"),
+    ("DK", r"^\d{8}$"),
+    ("EE", r"^\d{9}$"),
+    ("EL", r"^\d{9}$"),
+    ("ES", r"^[A-Z0-9]\d{7}[A-Z0-9]$"),
+    ("FI", r"^\d{8}$"),
+    ("FR", r"^[A-HJ-NP-Z0-9]{2}\d{9}$"),
+    ("GB", r"^\d{9}(?:\d{3})?$"),
+    ("XI", r"^\d{9}(?:\d{3})?$"),
+    ("HR", r"^\d{11}$"),
+    ("HU", r"^\d{8}$"),
+    ("IE", r"^(?:\d{7}[A-W][A-I]?|\d[A-Z+*]\d{5}[A-W])$"),
+    ("IT", r"^\d{11}$"),
+    ("LT", r"^(?:\d{9}|\d{12})$"),
+    ("LU", r"^\d{8}$"),
+    ("LV", r"^\d{11}$"),
+    ("MT", r"^\d{8}$"),
+    ("NL", r"^\d{9}B\d{2}$"),
+    ("PL", r"^\d{10}$"),
+    ("PT", r"^\d{9}$"),
+    ("RO", r"^\d{2,10}$"),
+    ("SE", r"^\d{10}01$"),
+    ("SI", r"^\d{8}$"),
+    ("SK", r"^\d{10}$"),
+    ("NO", r"^\d{9}MVA$"),
+];
+
+// Swiss UID, written with an E after the CH prefix
+const CH_VAT_FORMAT: &str = r"^E\d{9}(?:MWST|TVA|IVA)?$";
+
+// Countries that write a label in front of unprefixed VAT numbers
+const VAT_LABEL_COUNTRIES: [(&str, &[&str]); 10] = [
+    ("moms", &["DK", "SE"]),
+    ("mva", &["NO"]),
+    ("mwst", &["DE", "AT", "CH"]),
+    ("ust", &["DE", "AT"]),
+    ("btw", &["NL", "BE"]),
+    ("tva", &["FR", "BE", "LU", "CH"]),
+    ("iva", &["IT", "ES", "PT"]),
+    ("nip", &["PL"]),
+    ("alv", &["FI"]),
+    ("vat", &[]),
+];
+
+// Whether one of the three words before a match is a VAT label; "USt-IdNr." counts,
+// "customers" and "trust" don't
+fn is_vat_labelled(before: &str) -> bool {
+    let start = before.char_indices().rev().nth(40).map_or(0, |(ix, _)| ix);
+    let mut words: Vec<&str> = before[start..].split_whitespace().collect();
+    // the window may cut into its first word
+    if start > 0 && !words.is_empty() {
+        words.remove(0);
+    }
+    words
+        .iter()
+        .rev()
+        .take(3)
+        .flat_map(|word| word.split(|c: char| !c.is_alphanumeric()))
+        .any(|token| VAT_LABEL_COUNTRIES.iter().any(|(label, _)| token.eq_ignore_ascii_case(label)))
+}
+
+fn digits(number: &str) -> Vec<u32> {
+    number.chars().filter_map(|c| c.to_digit(10)).collect()
+}
+
+fn weighted_sum(digits: &[u32], weights: &[u32]) -> u32 {
+    digits.iter().zip(weights).map(|(d, w)| d * w).sum()
+}
+
+fn luhn_check(number: &str) -> bool {
+    let sum: u32 = digits(number)
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(ix, &d)| if ix % 2 == 1 { if d * 2 > 9 { d * 2 - 9 } else { d * 2 } } else { d })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+// CVR and Danish VAT
+fn dk_check(number: &str) -> bool {
+    let d = digits(number);
+    d.len() == 8 && weighted_sum(&d, &[2, 7, 6, 5, 4, 3, 2, 1]).is_multiple_of(11)
+}
+
+// Norwegian organisasjonsnummer
+fn no_check(number: &str) -> bool {
+    let d = digits(number);
+    if d.len() != 9 {
+        return false;
+    }
+    match 11 - weighted_sum(&d, &[3, 2, 7, 6, 5, 4, 3, 2]) % 11 {
+        11 => d[8] == 0,
+        10 => false,
+        check => d[8] == check,
+    }
+}
+
+// Finnish Y-tunnus
+fn fi_check(number: &str) -> bool {
+    let d = digits(number);
+    if d.len() != 8 {
+        return false;
+    }
+    match weighted_sum(&d, &[7, 9, 10, 5, 8, 4, 2]) % 11 {
+        0 => d[7] == 0,
+        1 => false,
+        rest => d[7] == 11 - rest,
+    }
+}
+
+// ISO 7064 MOD 11,10
+fn de_check(number: &str) -> bool {
+    let d = digits(number);
+    if d.len() != 9 {
+        return false;
+    }
+    let mut product = 10;
+    for digit in &d[..8] {
+        let mut sum = (digit + product) % 10;
+        if sum == 0 {
+            sum = 10;
+        }
+        product = (2 * sum) % 11;
+    }
+    (11 - product) % 10 == d[8]
+}
+
+// Old mod 11 numbers and the mod 97 numbers issued to sole traders since 2020
+fn nl_check(number: &str) -> bool {
+    let d = digits(number);
+    if d.len() != 11 {
+        return false;
+    }
+    let old = weighted_sum(&d[..8], &[9, 8, 7, 6, 5, 4, 3, 2]) % 11 == d[8];
+    // "NL" is 23 21 and "B" is 11 in ISO 7064 letter values
+    let numeric = format!("2321{}11{}{}", &number[..9], d[9], d[10]);
+    let new = numeric
+        .chars()
+        .fold(0, |rest, c| (rest * 10 + c.to_digit(10).unwrap_or(0)) % 97)
+        == 1;
+    old || new
+}
+
+fn be_check(number: &str) -> bool {
+    let Ok(value) = number.parse::<u64>() else {
+        return false;
+    };
+    97 - (value / 100) % 97 == value % 100
+}
+
+fn fr_check(number: &str) -> bool {
+    let (key, siren) = number.split_at(2);
+    match (key.parse::<u64>(), siren.parse::<u64>()) {
+        (Ok(key), Ok(siren)) => (12 + 3 * (siren % 97)) % 97 == key,
+        // letter keys use a different scheme
+        _ => false,
+    }
+}
+
+fn pl_check(number: &str) -> bool {
+    let d = digits(number);
+    d.len() == 10 && weighted_sum(&d, &[6, 5, 7, 2, 3, 4, 5, 6, 7]) % 11 == d[9]
+}
+
+fn pt_check(number: &str) -> bool {
+    let d = digits(number);
+    if d.len() != 9 {
+        return false;
+    }
+    let check = 11 - weighted_sum(&d, &[9, 8, 7, 6, 5, 4, 3, 2]) % 11;
+    d[8] == if check >= 10 { 0 } else { check }
+}
+
+fn at_check(number: &str) -> bool {
+    let d = digits(number);
+    if d.len() != 8 {
+        return false;
+    }
+    let doubled = |x: u32| (2 * x) / 10 + (2 * x) % 10;
+    let sum = d[0] + doubled(d[1]) + d[2] + doubled(d[3]) + d[4] + doubled(d[5]) + d[6];
+    (10 - (sum + 4) % 10) % 10 == d[7]
+}
+
+fn gb_check(number: &str) -> bool {
+    let d = digits(number);
+    if d.len() < 9 {
+        return false;
+    }
+    let total = weighted_sum(&d, &[8, 7, 6, 5, 4, 3, 2]) + d[7] * 10 + d[8];
+    total.is_multiple_of(97) || (total + 55).is_multiple_of(97)
+}
+
+fn ch_check(number: &str) -> bool {
+    let d = digits(number);
+    if d.len() != 9 {
+        return false;
+    }
+    match 11 - weighted_sum(&d, &[5, 4, 3, 2, 7, 6, 5, 4]) % 11 {
+        11 => d[8] == 0,
+        10 => false,
+        check => d[8] == check,
+    }
+}
+
+// None when the country has no checksum we know of
+fn vat_check(prefix: &str, number: &str) -> Option<bool> {
+    let valid = match prefix {
+        "AT" => at_check(number),
+        "BE" => be_check(number),
+        "CH" => ch_check(number),
+        "DE" => de_check(number),
+        "DK" => dk_check(number),
+        "FI" => fi_check(number),
+        "FR" => fr_check(number),
+        "GB" | "XI" => gb_check(number),
+        "IT" => luhn_check(number),
+        "NL" => nl_check(number),
+        "NO" => no_check(number),
+        "PL" => pl_check(number),
+        "PT" => pt_check(number),
+        "SE" => luhn_check(&number[..10]),
+        _ => return None,
+    };
+    Some(valid)
+}
+
+fn vat_format_matches(prefix: &str, number: &str) -> bool {
+    VAT_FORMAT_RES.get(prefix).is_some_and(|format| format.is_match(number))
+}
+
+fn country_of_prefix(prefix: &str) -> String {
+    match prefix {
+        "EL" => "GR".to_string(),
+        "XI" => "GB".to_string(),
+        _ => prefix.to_string(),
+    }
+}
+
+// Longest run of the captured characters that has the country's VAT format, with the
+// matching slice of the original text
+fn trim_to_vat_format(prefix: &str, raw: &str) -> Option<(String, String)> {
+    let compact: Vec<char> = raw.chars().filter(|c| c.is_ascii_alphanumeric()).collect();
+    for end in (1..=compact.len()).rev() {
+        let number: String = compact[..end].iter().collect();
+        if !vat_format_matches(prefix, &number) {
+            continue;
+        }
+        let mut seen = 0;
+        let original_end = raw
+            .char_indices()
+            .find_map(|(ix, c)| {
+                if c.is_ascii_alphanumeric() {
+                    seen += 1;
+                }
+                (seen == end).then_some(ix + c.len_utf8())
+            })
+            .unwrap_or(raw.len());
+        return Some((number, raw[..original_end].to_string()));
+    }
+    None
+}
+
+fn vat_id(prefix: &str, number: String, original: String) -> Option<CompanyId> {
+    let check_number = match prefix {
+        "AT" | "CH" => &number[1..],
+        "NO" => &number[..9],
+        "NL" => &number.replace('B', ""),
+        _ => &number,
+    };
+    let validated = match vat_check(prefix, check_number) {
+        Some(false) => return None,
+        Some(true) => true,
+        None => false,
+    };
+    Some(CompanyId {
+        kind: "vat".to_string(),
+        value: format!("{prefix}{number}"),
+        original,
+        country: country_of_prefix(prefix),
+        registry: None,
+        validated,
+    })
+}
+
+fn page_text(document: &NodeRef) -> String {
+    document
+        .inclusive_descendants()
+        .text_nodes()
+        .filter(|text_node| {
+            !text_node
+                .as_node()
+                .parent()
+                .and_then(|parent| parent.as_element().map(|e| e.name.local.to_string()))
+                .is_some_and(|tag| ["script", "style", "noscript"].contains(&tag.as_str()))
+        })
+        .map(|text_node| text_node.borrow().to_string())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+pub fn get_company_identifiers(document: &NodeRef, country_hint: Option<&str>) -> Vec<CompanyId> {
+    let country_hint = country_hint.map(|country| country.to_uppercase());
+    let text = page_text(document);
+    let mut result: Vec<CompanyId> = vec![];
+
+    for register in REGISTERS.iter() {
+        for captures in register.re.captures_iter(&text) {
+            let original = captures["number"].trim().to_string();
+            let mut value: String = original
+                .chars()
+                .filter(|c| c.is_ascii_alphanumeric())
+                .collect::<String>()
+                .to_uppercase();
+            let validated = match register.check {
+                Some(check) if !check(&value) => continue,
+                Some(_) => true,
+                None => false,
+            };
+            // Companies House numbers are 8 characters, zero padded after any prefix
+            if register.kind == "company_number" && value.len() < 8 {
+                let split = value.find(|c: char| c.is_ascii_digit()).unwrap_or(0);
+                let (letters, numbers) = value.split_at(split);
+                value = format!("{letters}{numbers:0>width$}", width = 8 - split);
+            }
+            result.push(CompanyId {
+                kind: register.kind.to_string(),
+                value,
+                original,
+                country: register.country.to_string(),
+                registry: None,
+                validated,
+            });
+        }
+    }
+
+    for captures in HANDELSREGISTER_RE.captures_iter(&text) {
+        let kind = &captures["kind"];
+        let number = captures["number"].trim();
+        result.push(CompanyId {
+            kind: kind.to_lowercase(),
+            value: format!("{kind}{}", number.replace(' ', "")),
+            original: format!("{kind} {number}"),
+            country: "DE".to_string(),
+            registry: captures.name("court").map(|court| court.as_str().to_string()),
+            validated: false,
+        });
+    }
+
+    for captures in VAT_PREFIXED_RE.captures_iter(&text) {
+        let prefix = &captures["prefix"];
+        let Some((number, raw)) = trim_to_vat_format(prefix, &captures["number"]) else {
+            continue;
+        };
+        let whole = captures.get(0).map_or("", |m| m.as_str());
+        let original = whole[..whole.len() - captures["number"].len() + raw.len()].to_string();
+        let before = &text[..captures.get(0).map_or(0, |m| m.start())];
+        let labelled = is_vat_labelled(before);
+        match vat_id(prefix, number, original) {
+            // a bare "DE123456789" may be an article number, so it needs a checksum or a label
+            Some(id) if id.validated || labelled => result.push(id),
+            _ => {}
+        }
+    }
+
+    for captures in VAT_LABEL_RE.captures_iter(&text) {
+        let label = captures["label"].to_lowercase();
+        let countries = VAT_LABEL_COUNTRIES
+            .iter()
+            .find(|(name, _)| *name == label)
+            .map_or(&[][..], |(_, countries)| *countries);
+        let country = match &country_hint {
+            Some(hint) if countries.is_empty() || countries.contains(&hint.as_str()) => {
+                hint.clone()
+            }
+            _ => match countries.first() {
+                Some(country) => country.to_string(),
+                None => continue,
+            },
+        };
+        let prefix = if country == "GR" { "EL".to_string() } else { country };
+        let original = captures["number"].trim().to_string();
+        let number: String = original.chars().filter(|c| c.is_ascii_alphanumeric()).collect();
+        // Swedish VAT is the organisationsnummer followed by 01
+        let number = match prefix.as_str() {
+            "SE" if number.len() == 10 => format!("{number}01"),
+            _ => number,
+        };
+        if !vat_format_matches(&prefix, &number) {
+            continue;
+        }
+        if let Some(id) = vat_id(&prefix, number, original) {
+            result.push(id);
+        }
+    }
+
+    let mut seen: HashSet<(String, String)> = HashSet::new();
+    result.retain(|id| seen.insert((id.kind.clone(), id.value.clone())));
+    result
+}
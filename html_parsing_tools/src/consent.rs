@@ -0,0 +1,132 @@
+use kuchiki::iter::NodeIterator;
+use kuchiki::{NodeRef, Selectors};
+use lazy_static::lazy_static;
+
+pub struct ConsentVendor {
+    pub name: &'static str,
+    /// the elements the vendor renders its banner, dialog and floating button into
+    pub containers: &'static [&'static str],
+    /// substrings of the `src` of the vendor's loader script
+    pub scripts: &'static [&'static str],
+}
+
+pub const CONSENT_VENDORS: [ConsentVendor; 15] = [
+    ConsentVendor {
+        name: "OneTrust",
+        containers: &["#onetrust-consent-sdk", "#onetrust-banner-sdk", "#ot-sdk-btn-floating"],
+        scripts: &["cdn.cookielaw.org", "optanon.blob.core.windows.net", "otsdkstub"],
+    },
+    ConsentVendor {
+        name: "Cookiebot",
+        containers: &["#CybotCookiebotDialog", "#CybotCookiebotDialogBodyUnderlay", "#CookiebotWidget"],
+        scripts: &["consent.cookiebot.com", "consent.cookiebot.eu"],
+    },
+    ConsentVendor {
+        name: "Usercentrics",
+        containers: &["#usercentrics-root", "#usercentrics-cmp-ui"],
+        scripts: &["app.usercentrics.eu", "web.cmp.usercentrics.eu"],
+    },
+    ConsentVendor {
+        name: "Didomi",
+        containers: &["#didomi-host", "#didomi-notice", "#didomi-popup"],
+        scripts: &["sdk.privacy-center.org"],
+    },
+    ConsentVendor {
+        name: "Quantcast",
+        containers: &["#qc-cmp2-container", ".qc-cmp2-container"],
+        scripts: &["cmp.quantcast.com", "quantcast.mgr.consensu.org"],
+    },
+    ConsentVendor {
+        name: "TrustArc",
+        containers: &["#truste-consent-track", "#consent_blackbar", ".truste_overlay", ".truste_box_overlay"],
+        scripts: &["consent.trustarc.com", "consent.truste.com"],
+    },
+    ConsentVendor {
+        name: "Sourcepoint",
+        containers: &["[id^=\"sp_message_container\"]"],
+        scripts: &["cdn.privacy-mgmt.com", "sourcepoint"],
+    },
+    ConsentVendor {
+        name: "Osano",
+        containers: &[".osano-cm-window"],
+        scripts: &["cmp.osano.com"],
+    },
+    ConsentVendor {
+        name: "CookieYes",
+        containers: &[".cky-consent-container", ".cky-modal", "#cookie-law-info-bar"],
+        scripts: &["cdn-cookieyes.com"],
+    },
+    ConsentVendor {
+        name: "Complianz",
+        containers: &["#cmplz-cookiebanner-container", ".cmplz-cookiebanner"],
+        scripts: &["complianz-gdpr"],
+    },
+    ConsentVendor {
+        name: "iubenda",
+        containers: &["#iubenda-cs-banner"],
+        scripts: &["cdn.iubenda.com"],
+    },
+    ConsentVendor {
+        name: "Termly",
+        containers: &["#termly-code-snippet-support"],
+        scripts: &["app.termly.io"],
+    },
+    ConsentVendor {
+        name: "Borlabs Cookie",
+        containers: &["#BorlabsCookieBox"],
+        scripts: &["borlabs-cookie"],
+    },
+    ConsentVendor {
+        name: "Cookie Notice",
+        containers: &["#cookie-notice"],
+        scripts: &["cookie-notice/js"],
+    },
+    // the open source cookieconsent banner, also shipped by many themes without a vendor
+    ConsentVendor {
+        name: "Cookie Consent",
+        containers: &[".cc-window", ".cc-revoke"],
+        scripts: &["cookieconsent.min.js", "cookieconsent2"],
+    },
+];
+
+lazy_static! {
+    // Each vendor's containers as one selector list, compiled once for every page
+    static ref VENDOR_CONTAINERS: Vec<Selectors> = CONSENT_VENDORS
+        .iter()
+        .map(|vendor| Selectors::compile(&vendor.containers.join(", ")).expect("Invalid Selector"))
+        .collect();
+}
+
+pub fn consent_containers() -> impl Iterator<Item = &'static str> {
+    CONSENT_VENDORS.iter().flat_map(|vendor| vendor.containers.iter().copied())
+}
+
+// Banners are often injected by the loader script after the page is served, so the
+// script is enough to name the vendor even when there is no container to remove
+pub fn detect_consent_vendor(document: &NodeRef) -> Option<String> {
+    let mut script_sources: Vec<String> = vec![];
+    let mut found: Vec<bool> = vec![false; CONSENT_VENDORS.len()];
+    for element in document.inclusive_descendants().elements() {
+        if &*element.name.local == "script" {
+            if let Some(src) = element.attributes.borrow().get("src") {
+                script_sources.push(src.to_lowercase());
+            }
+        }
+        for (ix, containers) in VENDOR_CONTAINERS.iter().enumerate() {
+            if !found[ix] && containers.matches(&element) {
+                found[ix] = true;
+            }
+        }
+    }
+    CONSENT_VENDORS
+        .iter()
+        .zip(found)
+        .find(|(vendor, has_container)| {
+            let has_script = vendor
+                .scripts
+                .iter()
+                .any(|needle| script_sources.iter().any(|src| src.contains(needle)));
+            *has_container || has_script
+        })
+        .map(|(vendor, _)| vendor.name.to_string())
+}
@@ -0,0 +1,232 @@
+use kuchiki::iter::NodeIterator;
+use kuchiki::{Node, NodeRef};
+use lazy_static::lazy_static;
+use pyo3::prelude::*;
+use regex::Regex;
+use std::collections::HashMap;
+
+lazy_static! {
+    static ref POSITIVE_RE: Regex = Regex::new(
+        r"(?i)article|body|content|entry|main|page|post|text|blog|story|hentry"
+    )
+    .expect("Invalid Regex");
+    static ref NEGATIVE_RE: Regex = Regex::new(
+        r"(?i)comment|footer|foot|nav|menu|sidebar|widget|banner|cookie|consent|gdpr|share|social|related|promo|sponsor|popup|modal|masthead|breadcrumb|newsletter|subscribe"
+    )
+    .expect("Invalid Regex");
+}
+
+// Elements whose own text counts as a paragraph of content
+const PARAGRAPH_TAGS: [&str; 6] = ["p", "pre", "td", "blockquote", "li", "dd"];
+// Parts of the page that are navigation or chrome wherever they are
+const BOILERPLATE_TAGS: [&str; 5] = ["header", "footer", "nav", "aside", "form"];
+// Elements whose text is never read
+const SKIP_TAGS: [&str; 6] = ["script", "style", "noscript", "template", "svg", "iframe"];
+const MIN_PARAGRAPH_LENGTH: usize = 25;
+
+#[pyclass(get_all, module = "html_parsing_tools")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MainContent {
+    /// paragraphs of the block separated by blank lines
+    pub text: String,
+    /// CSS selector of the block, e.g. "html > body > div#page > article"
+    pub path: String,
+    pub score: f64,
+    /// share of the block's text inside links
+    pub link_density: f64,
+    /// characters of text per element in the block
+    pub text_density: f64,
+}
+
+fn tag_name(node: &NodeRef) -> Option<String> {
+    node.as_element().map(|element| element.name.local.to_string())
+}
+
+fn is_skipped(node: &NodeRef) -> bool {
+    node.inclusive_ancestors()
+        .elements()
+        .any(|element| SKIP_TAGS.contains(&&*element.name.local))
+}
+
+fn text_length(node: &NodeRef) -> usize {
+    node.inclusive_descendants()
+        .text_nodes()
+        .filter(|text| !is_skipped(text.as_node()))
+        .map(|text| {
+            let text = text.borrow();
+            text.split_whitespace().map(|word| word.chars().count() + 1).sum::<usize>()
+        })
+        .sum()
+}
+
+pub fn link_density(node: &NodeRef) -> f64 {
+    let total = text_length(node);
+    if total == 0 {
+        return 0.0;
+    }
+    let linked: usize = node
+        .descendants()
+        .elements()
+        .filter(|element| &*element.name.local == "a")
+        .map(|element| text_length(element.as_node()))
+        .sum();
+    linked as f64 / total as f64
+}
+
+fn text_density(node: &NodeRef) -> f64 {
+    let elements = node
+        .descendants()
+        .elements()
+        .filter(|element| !SKIP_TAGS.contains(&&*element.name.local))
+        .count();
+    text_length(node) as f64 / (elements + 1) as f64
+}
+
+// Readability's starting score: containers up, lists and headings down, and the class
+// and id saying what the element is for
+fn initial_score(node: &NodeRef) -> f64 {
+    let Some(element) = node.as_element() else {
+        return 0.0;
+    };
+    let mut score: f64 = match &*element.name.local {
+        "article" | "main" => 10.0,
+        "div" | "section" => 5.0,
+        "pre" | "td" | "blockquote" => 3.0,
+        "address" | "ol" | "ul" | "dl" | "dd" | "dt" | "li" | "form" => -3.0,
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th" => -5.0,
+        _ => 0.0,
+    };
+    let attributes = element.attributes.borrow();
+    for attribute in ["class", "id", "role"] {
+        let value = attributes.get(attribute).unwrap_or("");
+        if value.is_empty() {
+            continue;
+        }
+        if NEGATIVE_RE.is_match(value) {
+            score -= 25.0;
+        }
+        if POSITIVE_RE.is_match(value) {
+            score += 25.0;
+        }
+    }
+    score
+}
+
+fn in_boilerplate(node: &NodeRef) -> bool {
+    node.inclusive_ancestors()
+        .filter_map(|ancestor| tag_name(&ancestor))
+        .any(|tag| BOILERPLATE_TAGS.contains(&tag.as_str()))
+}
+
+// Ids that can be written as `#id`: no leading digit, no punctuation to escape
+fn is_css_identifier(id: &str) -> bool {
+    let name = id.strip_prefix('-').unwrap_or(id);
+    name.chars().next().is_some_and(|c| !c.is_ascii_digit() && c != '-')
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || !c.is_ascii())
+}
+
+// A selector that finds the node again: ids where there are some, nth-of-type otherwise
+pub fn dom_path(node: &NodeRef) -> String {
+    let mut parts: Vec<String> = vec![];
+    for ancestor in node.inclusive_ancestors() {
+        let Some(element) = ancestor.as_element() else {
+            continue;
+        };
+        let tag = element.name.local.to_string();
+        if let Some(id) = element.attributes.borrow().get("id").filter(|id| is_css_identifier(id)) {
+            parts.push(format!("{tag}#{id}"));
+            continue;
+        }
+        let same_tag = |sibling: &NodeRef| tag_name(sibling).as_deref() == Some(tag.as_str());
+        let position = ancestor.preceding_siblings().filter(same_tag).count() + 1;
+        let total = position + ancestor.following_siblings().filter(same_tag).count();
+        if total > 1 {
+            parts.push(format!("{tag}:nth-of-type({position})"));
+        } else {
+            parts.push(tag);
+        }
+    }
+    parts.reverse();
+    parts.join(" > ")
+}
+
+// Paragraph text, one paragraph per block element
+fn clean_text(node: &NodeRef) -> String {
+    let mut paragraphs: Vec<String> = vec![];
+    let mut current = String::new();
+    for edge in node.traverse() {
+        let (start, node) = match edge {
+            kuchiki::iter::NodeEdge::Start(node) => (true, node),
+            kuchiki::iter::NodeEdge::End(node) => (false, node),
+        };
+        if let Some(text) = node.as_text() {
+            if start && !is_skipped(&node) {
+                current.push_str(&text.borrow());
+            }
+            continue;
+        }
+        let is_block = tag_name(&node).is_some_and(|tag| {
+            !["a", "span", "strong", "b", "em", "i", "u", "small", "sup", "sub", "abbr", "code"]
+                .contains(&tag.as_str())
+        });
+        if is_block {
+            let paragraph = current.split_whitespace().collect::<Vec<&str>>().join(" ");
+            if !paragraph.is_empty() {
+                paragraphs.push(paragraph);
+            }
+            current.clear();
+        }
+    }
+    paragraphs.join("\n\n")
+}
+
+fn key(node: &NodeRef) -> *const Node {
+    &**node as *const Node
+}
+
+pub fn find_main_content(document: &NodeRef) -> Option<MainContent> {
+    let mut scores: HashMap<*const Node, (NodeRef, f64)> = HashMap::new();
+    for paragraph in document.select(&PARAGRAPH_TAGS.join(", ")).unwrap() {
+        let paragraph = paragraph.as_node();
+        let length = text_length(paragraph);
+        if length < MIN_PARAGRAPH_LENGTH || link_density(paragraph) > 0.5 {
+            continue;
+        }
+        let text = paragraph.text_contents();
+        let commas = text.matches([',', '،', '、']).count() as f64;
+        let score = 1.0 + commas + (length as f64 / 100.0).min(3.0);
+
+        // the parent gets the whole score, the grandparent half, the next a third
+        for (level, ancestor) in paragraph.ancestors().take(3).enumerate() {
+            if ancestor.as_element().is_none() {
+                break;
+            }
+            let entry = scores
+                .entry(key(&ancestor))
+                .or_insert_with(|| (ancestor.clone(), initial_score(&ancestor)));
+            entry.1 += score / (level + 1) as f64;
+        }
+    }
+
+    let (node, score) = scores
+        .into_values()
+        .map(|(node, score)| {
+            let mut score = score * (1.0 - link_density(&node));
+            if in_boilerplate(&node) {
+                score -= 0.8 * score.abs();
+            }
+            (node, score)
+        })
+        .filter(|(node, _)| tag_name(node).as_deref() != Some("html"))
+        .max_by(|(a_node, a), (b_node, b)| {
+            a.total_cmp(b).then_with(|| text_length(b_node).cmp(&text_length(a_node)))
+        })?;
+
+    Some(MainContent {
+        text: clean_text(&node),
+        path: dom_path(&node),
+        score: (score * 100.0).round() / 100.0,
+        link_density: (link_density(&node) * 1000.0).round() / 1000.0,
+        text_density: (text_density(&node) * 10.0).round() / 10.0,
+    })
+}
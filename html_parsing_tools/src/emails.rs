@@ -0,0 +1,158 @@
+use crate::json_ld::decode_entities;
+use kuchiki::iter::NodeIterator;
+use kuchiki::NodeRef;
+use lazy_static::lazy_static;
+use linkify::{LinkFinder, LinkKind};
+use percent_encoding::percent_decode_str;
+use pyo3::prelude::*;
+use regex::Regex;
+
+lazy_static! {
+    // "name [at] domain [dot] com", "name(at)domain.com", "name at domain dot com"
+    static ref OBFUSCATED_EMAIL_RE: Regex = Regex::new(
+        r"(?i)\b(?P<local>[\w.+\-]+)\s*(?P<at>\[\s*(?:at|@)\s*\]|\(\s*(?:at|@)\s*\)|\{\s*(?:at|@)\s*\}|<\s*at\s*>|\s+at\s+|\s+@\s+)\s*(?P<domain>[\w\-]+(?:(?:\s*(?:\[\s*(?:dot|\.)\s*\]|\(\s*(?:dot|\.)\s*\)|\{\s*(?:dot|\.)\s*\})\s*|\s+dot\s+|\.)[\w\-]+)+)"
+    )
+    .expect("Invalid Regex");
+    static ref OBFUSCATED_DOT_RE: Regex = Regex::new(
+        r"(?i)\s*(?:\[\s*(?:dot|\.)\s*\]|\(\s*(?:dot|\.)\s*\)|\{\s*(?:dot|\.)\s*\})\s*|\s+dot\s+|\."
+    )
+    .expect("Invalid Regex");
+    // 'shop' + '@' + 'example.com'
+    static ref JS_CONCAT_RE: Regex = Regex::new(r#"['"]\s*\+\s*['"]"#).expect("Invalid Regex");
+    static ref CLOUDFLARE_HREF_RE: Regex =
+        Regex::new(r"/cdn-cgi/l/email-protection#(?P<hex>[0-9a-fA-F]+)").expect("Invalid Regex");
+}
+
+// Words that end up before a spelled out " at " in prose: "contact us at acme dot com"
+const PROSE_WORDS: [&str; 20] = [
+    "us", "me", "we", "you", "him", "her", "them", "it", "one", "here", "there", "online",
+    "available", "find", "visit", "see", "meet", "look", "open", "located",
+];
+
+#[pyclass(get_all, module = "html_parsing_tools")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Email {
+    /// lowercased domain, local part as written
+    pub address: String,
+    /// "mailto", "text", "attribute" (Cloudflare protection and data attributes) or "script"
+    pub source: String,
+}
+
+// Cloudflare's email protection: the first byte is the XOR key for the rest
+pub fn decode_cfemail(hex: &str) -> Option<String> {
+    let bytes: Vec<u8> = (0..hex.len())
+        .step_by(2)
+        .map(|ix| hex.get(ix..ix + 2).and_then(|b| u8::from_str_radix(b, 16).ok()))
+        .collect::<Option<Vec<u8>>>()?;
+    let (key, encoded) = bytes.split_first()?;
+    let decoded: Vec<u8> = encoded.iter().map(|b| b ^ key).collect();
+    String::from_utf8(decoded).ok()
+}
+
+fn find_emails(text: &str) -> Vec<String> {
+    let mut finder = LinkFinder::new();
+    finder.kinds(&[LinkKind::Email]);
+    finder
+        .links(text)
+        .map(|x| x.as_str().trim_matches(['\'', '.']).to_string())
+        .collect()
+}
+
+// Obfuscated addresses rewritten to plain ones; a plain " at " only counts when the dots
+// are spelled out as well and the word before it isn't prose, so "meet us at Main. Street"
+// and "find us at acme dot com" stay as they are
+pub fn deobfuscate(text: &str) -> String {
+    OBFUSCATED_EMAIL_RE
+        .replace_all(text, |captures: &regex::Captures| {
+            let at = captures["at"].trim().to_lowercase();
+            let domain = &captures["domain"];
+            let dots_spelled_out = OBFUSCATED_DOT_RE
+                .find_iter(domain)
+                .any(|m| m.as_str().trim() != ".");
+            let prose = PROSE_WORDS.contains(&captures["local"].to_lowercase().as_str());
+            if at == "at" && (!dots_spelled_out || prose) {
+                return captures[0].to_string();
+            }
+            let domain = OBFUSCATED_DOT_RE.replace_all(domain, ".");
+            format!("{}@{}", &captures["local"], domain)
+        })
+        .to_string()
+}
+
+fn normalize(address: &str) -> String {
+    match address.rsplit_once('@') {
+        Some((local, domain)) => format!("{local}@{}", domain.to_lowercase()),
+        None => address.to_string(),
+    }
+}
+
+fn is_code(node: &NodeRef) -> bool {
+    node.parent()
+        .and_then(|parent| parent.as_element().map(|e| e.name.local.to_string()))
+        .is_some_and(|tag| ["script", "style", "noscript"].contains(&tag.as_str()))
+}
+
+pub fn find_email_addresses(document: &NodeRef) -> Vec<Email> {
+    let mut found: Vec<(String, &str)> = vec![];
+
+    for element in document.select("*").unwrap() {
+        let attributes = element.attributes.borrow();
+        for (name, value) in attributes.map.iter() {
+            let name = name.local.as_ref();
+            let value = value.value.as_str();
+            if name == "href" {
+                if let Some(mailto) = value.trim().strip_prefix("mailto:") {
+                    let addresses = mailto.split('?').next().unwrap_or("");
+                    let addresses = percent_decode_str(addresses).decode_utf8_lossy();
+                    for address in find_emails(&addresses) {
+                        found.push((address, "mailto"));
+                    }
+                    continue;
+                }
+                if let Some(captures) = CLOUDFLARE_HREF_RE.captures(value) {
+                    let decoded = decode_cfemail(&captures["hex"]).unwrap_or_default();
+                    for address in find_emails(&decoded) {
+                        found.push((address, "attribute"));
+                    }
+                }
+                continue;
+            }
+            let value = match name {
+                "data-cfemail" => decode_cfemail(value).unwrap_or_default(),
+                _ if name.starts_with("on") => JS_CONCAT_RE.replace_all(value, "").to_string(),
+                _ => value.to_string(),
+            };
+            for address in find_emails(&deobfuscate(&value)) {
+                found.push((address, "attribute"));
+            }
+        }
+    }
+
+    for text_node in document.inclusive_descendants().text_nodes() {
+        let text = text_node.borrow().to_string();
+        if is_code(text_node.as_node()) {
+            let script = JS_CONCAT_RE.replace_all(&decode_entities(&text), "").to_string();
+            for address in find_emails(&script) {
+                found.push((address, "script"));
+            }
+        } else {
+            for address in find_emails(&deobfuscate(&text)) {
+                found.push((address, "text"));
+            }
+        }
+    }
+
+    // mailto links are the most deliberate, so they win when an address shows up twice
+    found.sort_by_key(|(_, source)| *source != "mailto");
+    let mut result: Vec<Email> = vec![];
+    for (address, source) in found {
+        let address = normalize(&address);
+        if !result.iter().any(|email| email.address.eq_ignore_ascii_case(&address)) {
+            result.push(Email {
+                address,
+                source: source.to_string(),
+            });
+        }
+    }
+    result
+}
@@ -0,0 +1,690 @@
+use kuchiki::NodeRef;
+use lazy_static::lazy_static;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList, PyString};
+use regex::Regex;
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+
+lazy_static! {
+    static ref CDATA_RE: Regex =
+        Regex::new(r"(?s)^\s*(?://\s*)?<!\[CDATA\[(.*?)(?://\s*)?\]\]>\s*$")
+            .expect("Invalid Regex");
+    static ref COMMENT_RE: Regex =
+        Regex::new(r"(?s)^\s*<!--(.*?)-->\s*$").expect("Invalid Regex");
+    static ref ENTITY_RE: Regex =
+        Regex::new(r"&(#[0-9]+|#[xX][0-9a-fA-F]+|[a-zA-Z]+);").expect("Invalid Regex");
+}
+
+// schema.org subtypes of LocalBusiness that show up on company sites
+const LOCAL_BUSINESS_TYPES: [&str; 30] = [
+    "LocalBusiness",
+    "AccountingService",
+    "AutoDealer",
+    "AutoRepair",
+    "AutomotiveBusiness",
+    "BeautySalon",
+    "Bakery",
+    "BarOrPub",
+    "CafeOrCoffeeShop",
+    "ChildCare",
+    "Dentist",
+    "DryCleaningOrLaundry",
+    "Electrician",
+    "FinancialService",
+    "FoodEstablishment",
+    "HairSalon",
+    "HealthAndBeautyBusiness",
+    "HomeAndConstructionBusiness",
+    "Hotel",
+    "LegalService",
+    "LodgingBusiness",
+    "MedicalBusiness",
+    "Plumber",
+    "ProfessionalService",
+    "RealEstateAgent",
+    "Restaurant",
+    "RoofingContractor",
+    "SportsActivityLocation",
+    "Store",
+    "TravelAgency",
+];
+
+// Most of the hundreds of other LocalBusiness subtypes are named after what they are:
+// "ClothingStore", "BikeShop", "HVACBusiness", "GeneralContractor"
+const LOCAL_BUSINESS_SUFFIXES: [&str; 5] = ["Store", "Shop", "Business", "Contractor", "Agency"];
+
+// Types that carry a name and an address or sameAs without being a company
+const NON_ORGANIZATION_TYPES: [&str; 12] = [
+    "WebSite",
+    "WebPage",
+    "CreativeWork",
+    "Article",
+    "NewsArticle",
+    "BlogPosting",
+    "Event",
+    "Place",
+    "Offer",
+    "ImageObject",
+    "VideoObject",
+    "Brand",
+];
+
+const ORGANIZATION_TYPES: [&str; 10] = [
+    "Organization",
+    "Corporation",
+    "EducationalOrganization",
+    "GovernmentOrganization",
+    "MedicalOrganization",
+    "NGO",
+    "NewsMediaOrganization",
+    "OnlineBusiness",
+    "OnlineStore",
+    "SportsOrganization",
+];
+
+#[pyclass(get_all, module = "html_parsing_tools")]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PostalAddress {
+    pub street_address: Option<String>,
+    pub postal_code: Option<String>,
+    pub locality: Option<String>,
+    pub region: Option<String>,
+    pub country: Option<String>,
+    pub post_office_box: Option<String>,
+}
+
+#[pyclass(get_all, module = "html_parsing_tools")]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Organization {
+    pub id: Option<String>,
+    pub types: Vec<String>,
+    pub name: Option<String>,
+    pub legal_name: Option<String>,
+    pub url: Option<String>,
+    pub logo: Option<String>,
+    pub description: Option<String>,
+    pub email: Option<String>,
+    pub telephone: Option<String>,
+    pub vat_id: Option<String>,
+    pub tax_id: Option<String>,
+    pub founding_date: Option<String>,
+    pub same_as: Vec<String>,
+    pub address: Option<PostalAddress>,
+}
+
+#[pyclass(get_all, module = "html_parsing_tools")]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LocalBusiness {
+    pub id: Option<String>,
+    pub types: Vec<String>,
+    pub name: Option<String>,
+    pub legal_name: Option<String>,
+    pub url: Option<String>,
+    pub logo: Option<String>,
+    pub description: Option<String>,
+    pub email: Option<String>,
+    pub telephone: Option<String>,
+    pub vat_id: Option<String>,
+    pub tax_id: Option<String>,
+    pub founding_date: Option<String>,
+    pub same_as: Vec<String>,
+    pub address: Option<PostalAddress>,
+    pub opening_hours: Vec<String>,
+    pub price_range: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+}
+
+#[pyclass(get_all, module = "html_parsing_tools")]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Product {
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub url: Option<String>,
+    pub image: Option<String>,
+    pub brand: Option<String>,
+    pub sku: Option<String>,
+    pub gtin: Option<String>,
+    pub mpn: Option<String>,
+    pub price: Option<String>,
+    pub price_currency: Option<String>,
+    pub availability: Option<String>,
+}
+
+#[pyclass(get_all, module = "html_parsing_tools")]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Person {
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub given_name: Option<String>,
+    pub family_name: Option<String>,
+    pub job_title: Option<String>,
+    pub email: Option<String>,
+    pub telephone: Option<String>,
+    pub url: Option<String>,
+    pub works_for: Option<String>,
+    pub same_as: Vec<String>,
+}
+
+#[pyclass(module = "html_parsing_tools")]
+#[derive(Default)]
+pub struct JsonLd {
+    /// text of every `application/ld+json` script, as found
+    #[pyo3(get)]
+    pub raw: Vec<String>,
+    /// `(script index, parse error)` for scripts that could not be repaired
+    #[pyo3(get)]
+    pub errors: Vec<(usize, String)>,
+    #[pyo3(get)]
+    pub organizations: Vec<Organization>,
+    #[pyo3(get)]
+    pub local_businesses: Vec<LocalBusiness>,
+    #[pyo3(get)]
+    pub products: Vec<Product>,
+    #[pyo3(get)]
+    pub people: Vec<Person>,
+    #[pyo3(get)]
+    pub addresses: Vec<PostalAddress>,
+    /// top level nodes, with `@graph` and arrays flattened
+    pub nodes: Vec<Value>,
+    /// every typed node, nested ones included, keyed by each of its types
+    pub by_type: HashMap<String, Vec<Value>>,
+}
+
+#[pymethods]
+impl JsonLd {
+    #[getter(nodes)]
+    fn py_nodes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        values_to_py(py, &self.nodes)
+    }
+
+    #[getter(by_type)]
+    fn py_by_type<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        for (type_name, nodes) in &self.by_type {
+            dict.set_item(type_name, values_to_py(py, nodes)?)?;
+        }
+        Ok(dict)
+    }
+
+    fn types(&self) -> Vec<String> {
+        let mut types: Vec<String> = self.by_type.keys().cloned().collect();
+        types.sort();
+        types
+    }
+}
+
+pub fn values_to_py<'py>(py: Python<'py>, values: &[Value]) -> PyResult<Bound<'py, PyList>> {
+    let list = PyList::empty(py);
+    for value in values {
+        list.append(value_to_py(py, value)?)?;
+    }
+    Ok(list)
+}
+
+fn value_to_py<'py>(py: Python<'py>, value: &Value) -> PyResult<Bound<'py, PyAny>> {
+    Ok(match value {
+        Value::Null => py.None().into_bound(py),
+        Value::Bool(b) => b.into_pyobject(py)?.to_owned().into_any(),
+        Value::Number(n) => match (n.as_i64(), n.as_f64()) {
+            (Some(i), _) => i.into_pyobject(py)?.into_any(),
+            (None, Some(f)) => f.into_pyobject(py)?.into_any(),
+            _ => PyString::new(py, &n.to_string()).into_any(),
+        },
+        Value::String(s) => PyString::new(py, s).into_any(),
+        Value::Array(items) => values_to_py(py, items)?.into_any(),
+        Value::Object(map) => {
+            let dict = PyDict::new(py);
+            for (key, item) in map {
+                dict.set_item(key, value_to_py(py, item)?)?;
+            }
+            dict.into_any()
+        }
+    })
+}
+
+// Unlike `get_json_ld`, keeps the script text untouched (html inside strings included)
+pub fn get_json_ld_scripts(document: &NodeRef) -> Vec<String> {
+    let mut result: Vec<String> = vec![];
+    for tag_node in document.select("script").unwrap() {
+        let attributes = tag_node.attributes.borrow();
+        let type_attribute = attributes.get("type").unwrap_or("").trim();
+        if type_attribute.eq_ignore_ascii_case("application/ld+json") {
+            result.push(tag_node.text_contents());
+        }
+    }
+    result
+}
+
+pub fn parse_json_ld_scripts(scripts: Vec<String>) -> JsonLd {
+    let mut result = JsonLd::default();
+
+    for (ix, script) in scripts.iter().enumerate() {
+        match parse_repaired(script) {
+            Ok(value) => flatten(value, &mut result.nodes),
+            Err(error) => result.errors.push((ix, error)),
+        }
+    }
+
+    let mut ids: HashMap<String, &Value> = HashMap::new();
+    for node in &result.nodes {
+        collect_ids(node, &mut ids);
+    }
+
+    let mut typed: Vec<&Value> = vec![];
+    for node in &result.nodes {
+        collect_typed(node, &mut typed);
+    }
+
+    let mut seen_addresses: HashSet<PostalAddress> = HashSet::new();
+    for node in typed {
+        let Some(obj) = node.as_object() else {
+            continue;
+        };
+        let types = node_types(obj);
+        for type_name in &types {
+            result
+                .by_type
+                .entry(type_name.clone())
+                .or_default()
+                .push(node.clone());
+        }
+
+        if types.iter().any(|t| is_local_business_type(t)) {
+            result.local_businesses.push(local_business(obj, types, &ids));
+        } else if types.iter().any(|t| ORGANIZATION_TYPES.contains(&t.as_str()))
+            || is_unlisted_organization(obj, &types)
+        {
+            result.organizations.push(organization(obj, types, &ids));
+        } else if types.iter().any(|t| t == "Product") {
+            result.products.push(product(obj));
+        } else if types.iter().any(|t| t == "Person") {
+            result.people.push(person(obj, &ids));
+        } else if types.iter().any(|t| t == "PostalAddress") {
+            let address = postal_address(obj);
+            if seen_addresses.insert(address.clone()) {
+                result.addresses.push(address);
+            }
+        }
+    }
+
+    result.raw = scripts;
+    result
+}
+
+// Try the text as is first, then with the usual breakages fixed
+fn parse_repaired(text: &str) -> Result<Value, String> {
+    let first_error = match serde_json::from_str::<Value>(text) {
+        Ok(value) => return Ok(value),
+        Err(error) => error.to_string(),
+    };
+
+    let mut unwrapped = text.trim().to_string();
+    for wrapper in [&*CDATA_RE, &*COMMENT_RE] {
+        if let Some(captures) = wrapper.captures(&unwrapped) {
+            unwrapped = captures[1].to_string();
+        }
+    }
+    if let Ok(value) = serde_json::from_str::<Value>(&repair_json(&unwrapped)) {
+        return Ok(value);
+    }
+    if ENTITY_RE.is_match(&unwrapped) {
+        let decoded = decode_entities(&unwrapped);
+        if let Ok(value) = serde_json::from_str::<Value>(&repair_json(&decoded)) {
+            return Ok(value);
+        }
+    }
+    Err(first_error)
+}
+
+// Drop trailing commas and escape raw control characters inside strings
+fn repair_json(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    let chars: Vec<char> = text.chars().collect();
+
+    for (ix, &c) in chars.iter().enumerate() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                '\n' => {
+                    result.push_str("\\n");
+                    continue;
+                }
+                '\r' => {
+                    result.push_str("\\r");
+                    continue;
+                }
+                '\t' => {
+                    result.push_str("\\t");
+                    continue;
+                }
+                _ => {}
+            }
+            result.push(c);
+            continue;
+        }
+
+        match c {
+            '"' => in_string = true,
+            ',' => {
+                let next = chars[ix + 1..].iter().find(|c| !c.is_whitespace());
+                if matches!(next, Some('}') | Some(']') | None) {
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        result.push(c);
+    }
+    result
+}
+
+pub fn decode_entities(text: &str) -> String {
+    ENTITY_RE
+        .replace_all(text, |captures: &regex::Captures| {
+            let entity = &captures[1];
+            let decoded = match entity {
+                "quot" => Some('"'),
+                "amp" => Some('&'),
+                "apos" => Some('\''),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "nbsp" => Some('\u{a0}'),
+                _ if entity.starts_with("#x") || entity.starts_with("#X") => {
+                    u32::from_str_radix(&entity[2..], 16).ok().and_then(char::from_u32)
+                }
+                _ if entity.starts_with('#') => {
+                    entity[1..].parse::<u32>().ok().and_then(char::from_u32)
+                }
+                _ => None,
+            };
+            match decoded {
+                Some(c) => c.to_string(),
+                None => captures[0].to_string(),
+            }
+        })
+        .to_string()
+}
+
+fn flatten(value: Value, nodes: &mut Vec<Value>) {
+    match value {
+        Value::Array(items) => {
+            for item in items {
+                flatten(item, nodes);
+            }
+        }
+        Value::Object(mut map) => {
+            if let Some(graph) = map.remove("@graph") {
+                flatten(graph, nodes);
+            }
+            if map.keys().any(|k| k != "@context") {
+                nodes.push(Value::Object(map));
+            }
+        }
+        _ => {}
+    }
+}
+
+fn collect_ids<'a>(value: &'a Value, ids: &mut HashMap<String, &'a Value>) {
+    match value {
+        Value::Array(items) => items.iter().for_each(|item| collect_ids(item, ids)),
+        Value::Object(map) => {
+            // a bare {"@id": ...} is a reference, not a definition
+            if map.len() > 1 {
+                if let Some(id) = map.get("@id").and_then(Value::as_str) {
+                    ids.entry(id.to_string()).or_insert(value);
+                }
+            }
+            map.values().for_each(|item| collect_ids(item, ids));
+        }
+        _ => {}
+    }
+}
+
+fn collect_typed<'a>(value: &'a Value, typed: &mut Vec<&'a Value>) {
+    match value {
+        Value::Array(items) => items.iter().for_each(|item| collect_typed(item, typed)),
+        Value::Object(map) => {
+            if map.contains_key("@type") {
+                typed.push(value);
+            }
+            map.values().for_each(|item| collect_typed(item, typed));
+        }
+        _ => {}
+    }
+}
+
+fn is_local_business_type(type_name: &str) -> bool {
+    LOCAL_BUSINESS_TYPES.contains(&type_name)
+        || (LOCAL_BUSINESS_SUFFIXES.iter().any(|suffix| type_name.ends_with(suffix))
+            && !ORGANIZATION_TYPES.contains(&type_name))
+}
+
+// "Airline", "Attorney", "Physician": a named node with an address or profiles is
+// taken to be an organization when none of its types is known to be something else
+fn is_unlisted_organization(obj: &Map<String, Value>, types: &[String]) -> bool {
+    let known = |t: &String| {
+        NON_ORGANIZATION_TYPES.contains(&t.as_str())
+            || ["Product", "Person", "PostalAddress"].contains(&t.as_str())
+    };
+    !types.is_empty()
+        && !types.iter().any(known)
+        && obj.contains_key("name")
+        && (obj.contains_key("address") || obj.contains_key("sameAs"))
+}
+
+// "schema:Organization" and "https://schema.org/Organization" both give "Organization"
+fn node_types(obj: &Map<String, Value>) -> Vec<String> {
+    let types = match obj.get("@type") {
+        Some(Value::String(s)) => vec![s.as_str()],
+        Some(Value::Array(items)) => items.iter().filter_map(Value::as_str).collect(),
+        _ => vec![],
+    };
+    types
+        .into_iter()
+        .map(|t| t.rsplit(['/', ':', '#']).next().unwrap_or(t).trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
+// Follow a bare {"@id": ...} reference to its definition
+fn resolve<'a>(value: &'a Value, ids: &HashMap<String, &'a Value>) -> &'a Value {
+    if let Value::Object(map) = value {
+        let target = map.get("@id").and_then(Value::as_str).and_then(|id| ids.get(id));
+        if let (1, Some(target)) = (map.len(), target) {
+            return target;
+        }
+    }
+    value
+}
+
+// Keys read from an object given where a text is expected, in order
+const TEXT_KEYS: [&str; 4] = ["name", "url", "@value", "@id"];
+// Same for URL fields, where an ImageObject's name is a caption rather than the image
+const URL_KEYS: [&str; 4] = ["url", "contentUrl", "@id", "@value"];
+
+fn text(value: Option<&Value>) -> Option<String> {
+    text_by(value, &TEXT_KEYS)
+}
+
+fn text_by(value: Option<&Value>, keys: &[&str]) -> Option<String> {
+    let text = match value? {
+        Value::String(s) => s.trim().to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::Array(items) => return items.iter().find_map(|item| text_by(Some(item), keys)),
+        Value::Object(map) => return keys.iter().find_map(|key| text_by(map.get(*key), keys)),
+        _ => return None,
+    };
+    if text.is_empty() {
+        None
+    } else {
+        Some(text)
+    }
+}
+
+fn urls(value: Option<&Value>) -> Vec<String> {
+    match value {
+        Some(Value::Array(items)) => {
+            items.iter().filter_map(|item| text_by(Some(item), &URL_KEYS)).collect()
+        }
+        value => text_by(value, &URL_KEYS).into_iter().collect(),
+    }
+}
+
+fn texts(value: Option<&Value>) -> Vec<String> {
+    match value {
+        Some(Value::Array(items)) => items.iter().filter_map(|item| text(Some(item))).collect(),
+        value => text(value).into_iter().collect(),
+    }
+}
+
+fn number(value: Option<&Value>) -> Option<f64> {
+    match value? {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn field(obj: &Map<String, Value>, key: &str) -> Option<String> {
+    text(obj.get(key))
+}
+
+fn url_field(obj: &Map<String, Value>, key: &str) -> Option<String> {
+    text_by(obj.get(key), &URL_KEYS)
+}
+
+fn email(obj: &Map<String, Value>) -> Option<String> {
+    field(obj, "email").map(|e| e.trim_start_matches("mailto:").to_string())
+}
+
+fn address(
+    obj: &Map<String, Value>,
+    ids: &HashMap<String, &Value>,
+) -> Option<PostalAddress> {
+    let value = obj.get("address")?;
+    let value = match value {
+        Value::Array(items) => items.first()?,
+        value => value,
+    };
+    match resolve(value, ids) {
+        Value::Object(map) => Some(postal_address(map)),
+        Value::String(s) if !s.trim().is_empty() => Some(PostalAddress {
+            street_address: Some(s.trim().to_string()),
+            ..Default::default()
+        }),
+        _ => None,
+    }
+}
+
+fn postal_address(obj: &Map<String, Value>) -> PostalAddress {
+    PostalAddress {
+        street_address: field(obj, "streetAddress"),
+        postal_code: field(obj, "postalCode"),
+        locality: field(obj, "addressLocality"),
+        region: field(obj, "addressRegion"),
+        country: field(obj, "addressCountry"),
+        post_office_box: field(obj, "postOfficeBoxNumber"),
+    }
+}
+
+fn organization(
+    obj: &Map<String, Value>,
+    types: Vec<String>,
+    ids: &HashMap<String, &Value>,
+) -> Organization {
+    Organization {
+        id: field(obj, "@id"),
+        types,
+        name: field(obj, "name"),
+        legal_name: field(obj, "legalName"),
+        url: url_field(obj, "url"),
+        logo: url_field(obj, "logo"),
+        description: field(obj, "description"),
+        email: email(obj),
+        telephone: field(obj, "telephone"),
+        vat_id: field(obj, "vatID"),
+        tax_id: field(obj, "taxID"),
+        founding_date: field(obj, "foundingDate"),
+        same_as: urls(obj.get("sameAs")),
+        address: address(obj, ids),
+    }
+}
+
+fn local_business(
+    obj: &Map<String, Value>,
+    types: Vec<String>,
+    ids: &HashMap<String, &Value>,
+) -> LocalBusiness {
+    let organization = organization(obj, types, ids);
+    let geo = obj.get("geo").map(|geo| resolve(geo, ids)).and_then(Value::as_object);
+    LocalBusiness {
+        id: organization.id,
+        types: organization.types,
+        name: organization.name,
+        legal_name: organization.legal_name,
+        url: organization.url,
+        logo: organization.logo,
+        description: organization.description,
+        email: organization.email,
+        telephone: organization.telephone,
+        vat_id: organization.vat_id,
+        tax_id: organization.tax_id,
+        founding_date: organization.founding_date,
+        same_as: organization.same_as,
+        address: organization.address,
+        opening_hours: texts(obj.get("openingHours")),
+        price_range: field(obj, "priceRange"),
+        latitude: geo.and_then(|geo| number(geo.get("latitude"))),
+        longitude: geo.and_then(|geo| number(geo.get("longitude"))),
+    }
+}
+
+fn product(obj: &Map<String, Value>) -> Product {
+    let offer = match obj.get("offers") {
+        Some(Value::Array(items)) => items.first().and_then(Value::as_object),
+        Some(Value::Object(map)) => Some(map),
+        _ => None,
+    };
+    let offer_field = |key: &str| offer.and_then(|offer| field(offer, key));
+    let gtin = ["gtin", "gtin13", "gtin14", "gtin12", "gtin8"]
+        .iter()
+        .find_map(|key| field(obj, key));
+
+    Product {
+        id: field(obj, "@id"),
+        name: field(obj, "name"),
+        description: field(obj, "description"),
+        url: url_field(obj, "url"),
+        image: url_field(obj, "image"),
+        brand: field(obj, "brand"),
+        sku: field(obj, "sku"),
+        gtin,
+        mpn: field(obj, "mpn"),
+        price: offer_field("price").or_else(|| offer_field("lowPrice")),
+        price_currency: offer_field("priceCurrency"),
+        availability: offer_field("availability")
+            .map(|a| a.rsplit('/').next().unwrap_or(&a).to_string()),
+    }
+}
+
+fn person(obj: &Map<String, Value>, ids: &HashMap<String, &Value>) -> Person {
+    Person {
+        id: field(obj, "@id"),
+        name: field(obj, "name"),
+        given_name: field(obj, "givenName"),
+        family_name: field(obj, "familyName"),
+        job_title: field(obj, "jobTitle"),
+        email: email(obj),
+        telephone: field(obj, "telephone"),
+        url: url_field(obj, "url"),
+        works_for: obj.get("worksFor").and_then(|w| text(Some(resolve(w, ids)))),
+        same_as: urls(obj.get("sameAs")),
+    }
+}
@@ -1,52 +1,40 @@
+mod addresses;
+mod company_ids;
+mod consent;
+mod content;
+mod emails;
+mod json_ld;
+mod markdown;
+mod phones;
+mod rules;
+mod social;
+mod tables;
 mod text_nodes;
 mod utils;
 
+use addresses::*;
+use company_ids::*;
+use consent::*;
+use content::*;
+use emails::*;
 use kuchiki::{iter::NodeIterator, traits::TendrilSink};
+use json_ld::*;
 use linkify::{LinkFinder, LinkKind};
-use pyo3::prelude::{pyclass, pyfunction, pymodule, wrap_pyfunction, Bound, PyModule, PyModuleMethods, PyResult};
+use markdown::*;
+use phones::*;
+use pyo3::prelude::{pyclass, pyfunction, pymodule, wrap_pyfunction, Bound, PyModule, PyModuleMethods, PyResult, Python};
+use pyo3::types::PyList;
 use rayon::prelude::*;
 use regex::RegexBuilder;
+use rules::*;
+use social::*;
+use tables::*;
 use std::collections::HashMap;
 use text_nodes::*;
 use utils::*;
 
 const REMOVE_TAGS_HTML_CONTENTS: [&str; 3] = ["script", "style", "noscript"];
 
-const REMOVE_TAGS: [&str; 27] = [
-    // scripts/styles
-    "script",
-    "style",
-    "noscript",
-    // COOKIE BANNERS
-    "#coiOverlay",
-    ".CookiesOK",
-    "#closeCookieBanner",
-    ".CookieBanner-button",
-    "#nts-set-cookie",
-    ".cc_btn_accept_all",
-    ".cookies",
-    ".noticeCookiesContent .CustomDismissCtrl",
-    ".cookie-consent .cookie-btn",
-    "#accept-cookies",
-    "#cookie_button_agree",
-    "#cookies-agreement #agree-button",
-    "#cookielayer .action-btn",
-    ".cookie.nag .close",
-    "#__tealiumGDPRecModal #consent_prompt_submit",
-    ".gdpr__button",
-    ".eu-cookie-compliance-agree-button",
-    ".cookie-notification .js-cookie-notification-hide",
-    ".js-accept-cookie-policy",
-    "#moove_gdpr_cookie_info_bar",
-    ".pea_cook_wrapper",
-    // testimonials
-    ".testimonial",
-    ".testimonial-text",
-    ".pwr-testimonial__quote", // hubspot
-];
-
-const PICK_TAGS: [&str; 6] = ["h1", "h2", "h3", "h4", "h5", "h6"];
-
 #[pyclass(get_all, module = "html_parsing_tools")]
 #[derive(Default)]
 struct GetSentencesResult {
@@ -57,10 +45,14 @@ struct GetSentencesResult {
     other: Vec<String>,
     p: Vec<String>,
     text_nodes: Vec<Vec<String>>,
+    /// consent manager found on the page, e.g. "OneTrust" or "Cookiebot"
+    consent_vendor: Option<String>,
 }
 
+/// `rules` is a `RemovalRules` object or the name of one given to `register_removal_rules`;
+/// the built-in rules apply without it
 #[pyfunction]
-#[pyo3(signature = (html, /, *, stop_word, remove_header, remove_footer, add_text_nodes, min_split_for_text_nodes))]
+#[pyo3(signature = (html, /, *, stop_word, remove_header, remove_footer, add_text_nodes, min_split_for_text_nodes, rules=None))]
 fn get_sentences(
     html: String,
     stop_word: &str,
@@ -68,6 +60,28 @@ fn get_sentences(
     remove_footer: bool,
     add_text_nodes: bool,
     min_split_for_text_nodes: Option<i32>,
+    rules: Option<RulesArg>,
+) -> PyResult<GetSentencesResult> {
+    let rules = resolve_rules(rules)?;
+    sentences_with_rules(
+        html,
+        stop_word,
+        remove_header,
+        remove_footer,
+        add_text_nodes,
+        min_split_for_text_nodes,
+        &rules,
+    )
+}
+
+fn sentences_with_rules(
+    html: String,
+    stop_word: &str,
+    remove_header: bool,
+    remove_footer: bool,
+    add_text_nodes: bool,
+    min_split_for_text_nodes: Option<i32>,
+    rules: &RemovalRules,
 ) -> PyResult<GetSentencesResult> {
     let mut result = GetSentencesResult::default();
 
@@ -77,22 +91,8 @@ fn get_sentences(
     if !json_ld.is_empty() {
         result.json_ld = json_ld;
     }
-    for tag in REMOVE_TAGS {
-        remove_tag(&document, tag);
-    }
-
-    if remove_header {
-        remove_tag(&document, "header");
-        remove_tag(&document, "nav");
-        remove_tag(&document, ".header");
-        remove_tag(&document, ".header-hero");
-    }
-
-    if remove_footer {
-        remove_tag(&document, "footer");
-        remove_tag(&document, ".footer");
-        remove_tag(&document, ".footer-hero");
-    }
+    result.consent_vendor = detect_consent_vendor(&document);
+    rules.apply(&document, remove_header, remove_footer);
 
     let stop_word_regex = RegexBuilder::new(stop_word)
         .case_insensitive(true)
@@ -111,11 +111,8 @@ fn get_sentences(
         }
     }
 
-    for tag in PICK_TAGS {
-        let text: Vec<String> = get_text_and_remove(&document, tag)
-            .iter()
-            .cloned()
-            .collect();
+    for tag in &rules.pick_tags {
+        let text: Vec<String> = get_text_and_remove(&document, tag);
 
         result
             .h
@@ -123,7 +120,7 @@ fn get_sentences(
     }
 
     let mut paragraphs: Vec<String> = get_text_and_remove(&document, "p");
-    paragraphs.sort_by(|a, b| count_words(b).cmp(&count_words(a)));
+    paragraphs.sort_by_key(|p| std::cmp::Reverse(count_words(p)));
 
     let paragraphs: Vec<String> = paragraphs
         .iter()
@@ -157,7 +154,7 @@ fn get_sentences(
 }
 
 #[pyfunction]
-#[pyo3(signature = (htmls, /, *, stop_word, remove_header, remove_footer, add_text_nodes, min_split_for_text_nodes))]
+#[pyo3(signature = (htmls, /, *, stop_word, remove_header, remove_footer, add_text_nodes, min_split_for_text_nodes, rules=None))]
 fn get_sentences_parallel(
     htmls: Vec<String>,
     stop_word: &str,
@@ -165,38 +162,57 @@ fn get_sentences_parallel(
     remove_footer: bool,
     add_text_nodes: bool,
     min_split_for_text_nodes: Option<i32>,
+    rules: Option<RulesArg>,
 ) -> PyResult<Vec<GetSentencesResult>> {
+    let rules = resolve_rules(rules)?;
     htmls
         .into_par_iter()
         .map(|html| {
-            get_sentences(
+            sentences_with_rules(
                 html,
                 stop_word,
                 remove_header,
                 remove_footer,
                 add_text_nodes,
                 min_split_for_text_nodes,
+                &rules,
             )
         })
         .collect()
 }
 
+/// Stores `rules` under `name` for `get_sentences(..., rules=name)`, replacing any earlier set
 #[pyfunction]
-fn get_href_attributes(html: String) -> PyResult<Vec<String>> {
+fn register_removal_rules(name: String, rules: RemovalRules) {
+    register_rules(name, rules);
+}
+
+/// Returns whether a rule set with that name existed
+#[pyfunction]
+fn unregister_removal_rules(name: &str) -> bool {
+    unregister_rules(name)
+}
+
+#[pyfunction]
+fn removal_rule_names() -> Vec<String> {
+    rule_names()
+}
+
+#[pyfunction]
+#[pyo3(signature = (html, page_url=None))]
+fn get_href_attributes(html: String, page_url: Option<&str>) -> PyResult<Vec<String>> {
     let document = kuchiki::parse_html().one(html);
-    // let mut links: Vec<String> = vec![];
+    let base_url = get_base_url(&document, page_url);
 
     let links: Vec<String> = document
         .select("a")
         .unwrap()
-        // .collect()
         .map(|x| {
             let attributes = x.attributes.borrow();
-            let href = attributes.get("href");
-            if href.is_none() {
-                return "".to_string();
+            match attributes.get("href") {
+                Some(href) => resolve_url(base_url.as_ref(), href),
+                None => "".to_string(),
             }
-            href.unwrap().to_string()
         })
         .collect();
 
@@ -204,28 +220,95 @@ fn get_href_attributes(html: String) -> PyResult<Vec<String>> {
 }
 
 #[pyfunction]
-fn get_links(html: String) -> PyResult<Vec<(String, String)>> {
+#[pyo3(signature = (html, page_url=None))]
+fn get_links(html: String, page_url: Option<&str>) -> PyResult<Vec<(String, String)>> {
     let document = kuchiki::parse_html().one(html);
-    // let mut links: Vec<String> = vec![];
+    let base_url = get_base_url(&document, page_url);
 
     let links: Vec<(String, String)> = document
         .select("a")
         .unwrap()
-        // .collect()
         .map(|x| {
             let attributes = x.attributes.borrow();
-            let href = attributes.get("href");
             let text = get_text_string(x.as_node(), " ");
-            if href.is_none() {
-                return ("".to_string(), text);
+            match attributes.get("href") {
+                Some(href) => (resolve_url(base_url.as_ref(), href), text),
+                None => ("".to_string(), text),
             }
-            return (href.unwrap().to_string(), text);
         })
         .collect();
 
     Ok(links)
 }
 
+#[pyclass(get_all, module = "html_parsing_tools")]
+#[derive(Clone)]
+struct Link {
+    url: String,
+    text: String,
+    /// internal, external, mailto, tel, anchor or javascript
+    kind: String,
+}
+
+#[pyfunction]
+#[pyo3(signature = (html, page_url=None))]
+fn get_classified_links(html: String, page_url: Option<&str>) -> PyResult<Vec<Link>> {
+    let document = kuchiki::parse_html().one(html);
+    let base_url = get_base_url(&document, page_url);
+    // internal means same host as the page, falling back to <base href> without a page URL
+    let page_host = page_url
+        .and_then(|url| url::Url::parse(url).ok())
+        .or_else(|| base_url.clone())
+        .and_then(|url| url.host_str().map(|host| host.to_string()));
+
+    let links: Vec<Link> = document
+        .select("a[href]")
+        .unwrap()
+        .map(|x| {
+            let attributes = x.attributes.borrow();
+            let href = attributes.get("href").unwrap_or("");
+            let url = resolve_url(base_url.as_ref(), href);
+            Link {
+                kind: classify_link(href, &url, page_host.as_deref()).to_string(),
+                text: get_text_string(x.as_node(), " "),
+                url,
+            }
+        })
+        .collect();
+
+    Ok(links)
+}
+
+/// Company profiles on LinkedIn, Facebook, X, Instagram, YouTube, GitHub and TikTok, from
+/// JSON-LD `sameAs` and from links, as canonical URLs. Share and intent links are left out
+#[pyfunction]
+#[pyo3(signature = (html, page_url=None))]
+fn get_social_profiles(html: String, page_url: Option<&str>) -> PyResult<Vec<SocialProfile>> {
+    let document = kuchiki::parse_html().one(html);
+    let base_url = get_base_url(&document, page_url);
+
+    let json_ld = parse_json_ld_scripts(get_json_ld_scripts(&document));
+    let same_as: Vec<String> = json_ld
+        .organizations
+        .iter()
+        .flat_map(|organization| organization.same_as.iter())
+        .chain(json_ld.local_businesses.iter().flat_map(|business| business.same_as.iter()))
+        .chain(json_ld.people.iter().flat_map(|person| person.same_as.iter()))
+        .cloned()
+        .collect();
+    let links: Vec<String> = document
+        .select("a[href], link[href]")
+        .unwrap()
+        .map(|x| resolve_url(base_url.as_ref(), x.attributes.borrow().get("href").unwrap_or("")))
+        .collect();
+
+    let urls = same_as
+        .iter()
+        .map(|url| (url.as_str(), "json_ld"))
+        .chain(links.iter().map(|url| (url.as_str(), "link")));
+    Ok(find_social_profiles(urls))
+}
+
 #[pyfunction]
 fn get_emails(html: String) -> PyResult<Vec<String>> {
     let mut finder = LinkFinder::new();
@@ -237,6 +320,133 @@ fn get_emails(html: String) -> PyResult<Vec<String>> {
     Ok(links)
 }
 
+/// Unique email addresses with where they were found, including ones hidden behind
+/// "[at]"/"[dot]", HTML entities, Cloudflare email protection and JS string concatenation
+#[pyfunction]
+fn get_email_addresses(html: String) -> PyResult<Vec<Email>> {
+    let document = kuchiki::parse_html().one(html);
+    Ok(find_email_addresses(&document))
+}
+
+#[pyclass(get_all, module = "html_parsing_tools")]
+#[derive(Default)]
+struct GetMetaResult {
+    title: String,
+    og: HashMap<String, Vec<String>>,
+    twitter: HashMap<String, Vec<String>>,
+    article: HashMap<String, Vec<String>>,
+    product: HashMap<String, Vec<String>>,
+    fb: HashMap<String, Vec<String>>,
+    dublin_core: HashMap<String, Vec<String>>,
+    http_equiv: HashMap<String, Vec<String>>,
+    other: HashMap<String, Vec<String>>,
+}
+
+#[pyfunction]
+fn get_meta(html: String) -> PyResult<GetMetaResult> {
+    let document = kuchiki::parse_html().one(html);
+    let mut result = GetMetaResult::default();
+
+    for (key, content) in get_meta_values(&document) {
+        let group = match key.split([':', '.']).next().unwrap_or("") {
+            _ if key.starts_with("http-equiv:") => &mut result.http_equiv,
+            "og" => &mut result.og,
+            "twitter" => &mut result.twitter,
+            "article" => &mut result.article,
+            "product" => &mut result.product,
+            "fb" => &mut result.fb,
+            "dc" | "dcterms" => &mut result.dublin_core,
+            _ => &mut result.other,
+        };
+        let key = key.trim_start_matches("http-equiv:").to_string();
+        let values = group.entry(key).or_default();
+        if !values.contains(&content) {
+            values.push(content);
+        }
+    }
+
+    if let Ok(title) = document.select_first("title") {
+        result.title = get_text_string(title.as_node(), " ");
+    }
+
+    Ok(result)
+}
+
+/// `country` is an ISO 3166 alpha-2 hint for national numbers; defaults to the page language
+#[pyfunction]
+#[pyo3(signature = (html, country=None))]
+fn get_phones(html: String, country: Option<String>) -> PyResult<Vec<Phone>> {
+    let document = kuchiki::parse_html().one(html);
+    let country = country.or_else(|| country_from_lang(&get_lang_internal(&document)));
+    Ok(get_phone_numbers(&document, country.as_deref()))
+}
+
+/// VAT numbers and national company register numbers, checksum-validated where the scheme has one.
+/// `country` is an ISO 3166 alpha-2 hint for unprefixed VAT numbers; defaults to the page language
+#[pyfunction]
+#[pyo3(signature = (html, country=None))]
+fn get_company_ids(html: String, country: Option<String>) -> PyResult<Vec<CompanyId>> {
+    let document = kuchiki::parse_html().one(html);
+    let country = country.or_else(|| country_from_lang(&get_lang_internal(&document)));
+    Ok(get_company_identifiers(&document, country.as_deref()))
+}
+
+/// Postal addresses from structured data and from the page text, best candidate first.
+/// `country` is an ISO 3166 alpha-2 hint; defaults to the page language
+#[pyfunction]
+#[pyo3(signature = (html, country=None))]
+fn get_addresses(html: String, country: Option<String>) -> PyResult<Vec<AddressCandidate>> {
+    let document = kuchiki::parse_html().one(html);
+    let country = country.or_else(|| country_from_lang(&get_lang_internal(&document)));
+
+    let json_ld = parse_json_ld_scripts(get_json_ld_scripts(&document));
+    let mut candidates = find_json_ld_addresses(&json_ld, country.as_deref());
+    candidates.extend(find_item_addresses(&get_microdata_items(&document, None), "microdata"));
+    candidates.extend(find_item_addresses(&get_rdfa_items(&document, None), "rdfa"));
+
+    for tag in REMOVE_TAGS_HTML_CONTENTS {
+        remove_tag(&document, tag);
+    }
+    let text_nodes: Vec<String> = document
+        .inclusive_descendants()
+        .text_nodes()
+        .map(|text_node| text_node.borrow().to_string())
+        .collect();
+    if let Some(grouped_text_nodes) = group_text_nodes(&text_nodes, None) {
+        candidates.extend(find_text_addresses(&grouped_text_nodes, country.as_deref()));
+    }
+
+    Ok(merge_addresses(candidates))
+}
+
+/// Same as `get_addresses` for text already grouped by `get_sentences(add_text_nodes=True)`
+#[pyfunction]
+#[pyo3(signature = (text_nodes, country=None))]
+fn get_text_node_addresses(
+    text_nodes: Vec<Vec<String>>,
+    country: Option<String>,
+) -> PyResult<Vec<AddressCandidate>> {
+    Ok(merge_addresses(find_text_addresses(&text_nodes, country.as_deref())))
+}
+
+/// The block holding the page's main text, found by scoring paragraphs on text length and
+/// link density and penalizing header, footer, nav and aside; None for pages without prose
+#[pyfunction]
+fn get_main_content(html: String) -> PyResult<Option<MainContent>> {
+    let document = kuchiki::parse_html().one(html);
+    Ok(find_main_content(&document))
+}
+
+/// Every table as headers plus a row matrix, with spans expanded and layout tables flagged
+#[pyfunction]
+fn get_tables(html: String) -> PyResult<Vec<Table>> {
+    let document = kuchiki::parse_html().one(html);
+    for tag in REMOVE_TAGS_HTML_CONTENTS {
+        remove_tag(&document, tag);
+    }
+    Ok(get_table_list(&document))
+}
+
 #[pyfunction]
 fn get_meta_titles(html: String) -> PyResult<HashMap<String, String>> {
     let document = kuchiki::parse_html().one(html);
@@ -244,7 +454,11 @@ fn get_meta_titles(html: String) -> PyResult<HashMap<String, String>> {
     let tag_nodes = document.select("meta").unwrap();
     for tag_node in tag_nodes.collect::<Vec<_>>() {
         let attributes: std::cell::Ref<kuchiki::Attributes> = tag_node.attributes.borrow();
-        let name_attribute = attributes.get("name").unwrap_or("");
+        // OpenGraph uses `property`, Twitter Cards use `name`, and sites mix them up
+        let name_attribute = attributes
+            .get("property")
+            .or_else(|| attributes.get("name"))
+            .unwrap_or("");
         if name_attribute == "twitter:title" || name_attribute == "og:title" {
             let content = attributes.get("content").unwrap_or("").to_string();
             if content.is_empty() {
@@ -268,20 +482,23 @@ fn get_meta_titles(html: String) -> PyResult<HashMap<String, String>> {
 #[pyfunction]
 fn tag_attribute(html: String, tag: String, attribute: String) -> PyResult<String> {
     let document = kuchiki::parse_html().one(html);
-    let tag_nodes: kuchiki::iter::Select<kuchiki::iter::Elements<kuchiki::iter::Descendants>> =
-        document.select(tag.as_str()).unwrap();
-    for tag_node in tag_nodes.collect::<Vec<_>>() {
-        let attributes: std::cell::Ref<kuchiki::Attributes> = tag_node.attributes.borrow();
-        return Ok(attributes.get(attribute).unwrap_or("").to_string());
-    }
-
-    Ok("".to_string())
+    let tag_node = match document.select_first(tag.as_str()) {
+        Ok(tag_node) => tag_node,
+        Err(_) => return Ok("".to_string()),
+    };
+    let attributes: std::cell::Ref<kuchiki::Attributes> = tag_node.attributes.borrow();
+    Ok(attributes.get(attribute).unwrap_or("").to_string())
 }
 
 #[pyfunction]
-fn get_alternate_links(html: String) -> PyResult<HashMap<String, Vec<String>>> {
+#[pyo3(signature = (html, page_url=None))]
+fn get_alternate_links(
+    html: String,
+    page_url: Option<&str>,
+) -> PyResult<HashMap<String, Vec<String>>> {
     let document = kuchiki::parse_html().one(html);
-    Ok(get_rel_alternate(&document))
+    let base_url = get_base_url(&document, page_url);
+    Ok(get_rel_alternate(&document, base_url.as_ref()))
 }
 
 #[pyfunction]
@@ -293,6 +510,47 @@ fn html_contents(html: String) -> PyResult<String> {
     Ok(document.to_string())
 }
 
+/// The page body as Markdown, after the same cleanup as `get_sentences`.
+/// `links` is "inline", "reference" or "text"; `images` is "markdown", "alt" or "none"
+#[pyfunction]
+#[pyo3(signature = (html, links="inline", images="alt", page_url=None))]
+fn html_to_markdown(
+    html: String,
+    links: &str,
+    images: &str,
+    page_url: Option<&str>,
+) -> PyResult<String> {
+    let links = match links {
+        "inline" => LinkStyle::Inline,
+        "reference" => LinkStyle::Reference,
+        "text" => LinkStyle::Text,
+        other => {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "unknown links option: {other}"
+            )))
+        }
+    };
+    let images = match images {
+        "markdown" => ImageStyle::Markdown,
+        "alt" => ImageStyle::Alt,
+        "none" => ImageStyle::None,
+        other => {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "unknown images option: {other}"
+            )))
+        }
+    };
+
+    let document = kuchiki::parse_html().one(html);
+    let base_url = get_base_url(&document, page_url);
+    RemovalRules::default().apply(&document, false, false);
+    let body = match document.select_first("body") {
+        Ok(body) => body.as_node().clone(),
+        Err(_) => document,
+    };
+    Ok(MarkdownWriter::new(links, images, base_url).convert(&body))
+}
+
 #[pyfunction]
 fn tag_html_contents(html: String, tag: String) -> PyResult<String> {
     let document = kuchiki::parse_html().one(html);
@@ -305,6 +563,28 @@ fn tag_html_contents(html: String, tag: String) -> PyResult<String> {
     Ok(res)
 }
 
+#[pyfunction]
+fn parse_json_ld(html: String) -> PyResult<JsonLd> {
+    let document = kuchiki::parse_html().one(html);
+    Ok(parse_json_ld_scripts(get_json_ld_scripts(&document)))
+}
+
+#[pyfunction]
+#[pyo3(signature = (html, page_url=None))]
+fn get_microdata<'py>(py: Python<'py>, html: String, page_url: Option<&str>) -> PyResult<Bound<'py, PyList>> {
+    let document = kuchiki::parse_html().one(html);
+    let base_url = get_base_url(&document, page_url);
+    values_to_py(py, &get_microdata_items(&document, base_url.as_ref()))
+}
+
+#[pyfunction]
+#[pyo3(signature = (html, page_url=None))]
+fn get_rdfa<'py>(py: Python<'py>, html: String, page_url: Option<&str>) -> PyResult<Bound<'py, PyList>> {
+    let document = kuchiki::parse_html().one(html);
+    let base_url = get_base_url(&document, page_url);
+    values_to_py(py, &get_rdfa_items(&document, base_url.as_ref()))
+}
+
 #[pyfunction]
 fn get_lang(html: String) -> PyResult<String> {
     let document = kuchiki::parse_html().one(html);
@@ -337,7 +617,7 @@ mod tests {
 
     #[test]
     fn test_get_sentences() {
-        let result = get_sentences(HTML.to_string(), "_stop_", false, false, true, None).unwrap();
+        let result = get_sentences(HTML.to_string(), "_stop_", false, false, true, None, None).unwrap();
 
         assert_eq!(
             result.text_nodes,
@@ -393,6 +673,7 @@ mod tests {
             false,
             true,
             None,
+            None,
         )
         .unwrap();
         assert!(result.text_nodes.is_empty());
@@ -407,6 +688,7 @@ mod tests {
             false,
             true,
             None,
+            None,
         )
         .unwrap();
         assert_eq!(result.len(), 2);
@@ -434,6 +716,732 @@ mod tests {
         assert_eq!(result[1].text_nodes, text_nodes);
     }
 
+    #[test]
+    fn test_parse_json_ld() {
+        let html = r#"
+            <script type="application/ld+json">
+            {"@context": "https://schema.org", "@graph": [
+                {"@type": "WebSite", "@id": "https://home.dk/#website",
+                 "publisher": {"@id": "https://home.dk/#org"}},
+                {"@type": ["Organization", "Corporation"], "@id": "https://home.dk/#org",
+                 "name": "home a/s", "legalName": "home a/s", "vatID": "DK13394172",
+                 "email": "mailto:homeas@home.dk", "logo": {"@type": "ImageObject", "name": "home a/s logo", "url": "https://home.dk/logo.png"},
+                 "sameAs": ["https://www.facebook.com/home", "https://www.linkedin.com/company/home"],
+                 "address": {"@id": "https://home.dk/#address"}},
+                {"@type": "PostalAddress", "@id": "https://home.dk/#address",
+                 "streetAddress": "Søren Frichs Vej 36 F", "postalCode": "8230",
+                 "addressLocality": "Åbyhøj", "addressCountry": {"@type": "Country", "name": "DK"}},
+            ]}
+            </script>
+            <script type="application/ld+json">//<![CDATA[
+            {"@context": "http://schema.org", "@type": "schema:Restaurant", "name": "Café
+            Nord", "geo": {"@type": "GeoCoordinates", "latitude": "56.15", "longitude": 10.2},
+             "openingHours": ["Mo-Fr 10:00-22:00"], "address": "Nørregade 1, 8000 Aarhus C",}
+            //]]></script>
+            <script type="application/ld+json">
+            [{&quot;@context&quot;: &quot;https://schema.org&quot;, &quot;@type&quot;: &quot;Person&quot;,
+              &quot;name&quot;: &quot;Søren Frich&quot;, &quot;jobTitle&quot;: &quot;CEO&quot;,
+              &quot;worksFor&quot;: {&quot;@id&quot;: &quot;https://home.dk/#org&quot;}}]
+            </script>
+            <script type="application/ld+json">
+            {"@type": "Product", "name": "Sofa", "gtin13": "5701234567890",
+             "brand": {"@type": "Brand", "name": "home"},
+             "offers": {"@type": "Offer", "price": 4999, "priceCurrency": "DKK",
+                        "availability": "https://schema.org/InStock"}}
+            </script>
+            <script type="application/ld+json">{"@type": "Thing", "name": </script>
+        "#;
+        let document = kuchiki::parse_html().one(html);
+        let result = parse_json_ld_scripts(get_json_ld_scripts(&document));
+
+        assert_eq!(result.raw.len(), 5);
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].0, 4);
+        assert_eq!(result.nodes.len(), 6);
+
+        let organization = &result.organizations[0];
+        assert_eq!(organization.types, ["Organization", "Corporation"]);
+        assert_eq!(organization.vat_id.as_deref(), Some("DK13394172"));
+        assert_eq!(organization.email.as_deref(), Some("homeas@home.dk"));
+        assert_eq!(organization.logo.as_deref(), Some("https://home.dk/logo.png"));
+        assert_eq!(organization.same_as.len(), 2);
+        let address = organization.address.as_ref().unwrap();
+        assert_eq!(address.postal_code.as_deref(), Some("8230"));
+        assert_eq!(address.country.as_deref(), Some("DK"));
+        assert_eq!(result.addresses.len(), 1);
+        assert_eq!(&result.addresses[0], address);
+
+        let business = &result.local_businesses[0];
+        assert_eq!(business.types, ["Restaurant"]);
+        assert_eq!(business.name.as_deref(), Some("Café\n            Nord"));
+        assert_eq!(business.latitude, Some(56.15));
+        assert_eq!(business.longitude, Some(10.2));
+        assert_eq!(
+            business.address.as_ref().unwrap().street_address.as_deref(),
+            Some("Nørregade 1, 8000 Aarhus C")
+        );
+
+        let person = &result.people[0];
+        assert_eq!(person.job_title.as_deref(), Some("CEO"));
+        assert_eq!(person.works_for.as_deref(), Some("home a/s"));
+
+        let product = &result.products[0];
+        assert_eq!(product.gtin.as_deref(), Some("5701234567890"));
+        assert_eq!(product.brand.as_deref(), Some("home"));
+        assert_eq!(product.price.as_deref(), Some("4999"));
+        assert_eq!(product.availability.as_deref(), Some("InStock"));
+
+        assert_eq!(result.by_type["Organization"].len(), 1);
+        assert_eq!(result.by_type["Corporation"].len(), 1);
+        assert!(result.by_type.contains_key("ImageObject"));
+        assert!(result.by_type.contains_key("Country"));
+
+        // subtypes that aren't listed by name
+        let html = r#"<script type="application/ld+json">[
+            {"@type": "ClothingStore", "name": "Acme Tøj", "sameAs": "https://instagram.com/acmetoj"},
+            {"@type": "Airline", "name": "Acme Air", "address": {"@type": "PostalAddress",
+             "postalCode": "2770", "addressLocality": "Kastrup"}},
+            {"@type": "WebSite", "name": "Acme", "sameAs": "https://x.com/acme"}]</script>"#;
+        let document = kuchiki::parse_html().one(html);
+        let result = parse_json_ld_scripts(get_json_ld_scripts(&document));
+        assert_eq!(result.local_businesses.len(), 1);
+        assert_eq!(result.local_businesses[0].same_as, ["https://instagram.com/acmetoj"]);
+        assert_eq!(result.organizations.len(), 1);
+        assert_eq!(result.organizations[0].types, ["Airline"]);
+        assert_eq!(
+            result.organizations[0].address.as_ref().unwrap().locality.as_deref(),
+            Some("Kastrup")
+        );
+    }
+
+    #[test]
+    fn test_get_microdata() {
+        let html = r#"
+            <base href="/da/">
+            <div itemscope itemtype="https://schema.org/LocalBusiness" itemref="hours">
+                <h1 itemprop="name">home   a/s</h1>
+                <img itemprop="logo image" src="logo.png">
+                <a itemprop="url" href="https://home.dk">home.dk</a>
+                <div itemprop="address" itemscope itemtype="https://schema.org/PostalAddress">
+                    <span itemprop="streetAddress">Søren Frichs Vej 36 F</span>,
+                    <span itemprop="postalCode">8230</span>
+                    <span itemprop="addressLocality">Åbyhøj</span>
+                </div>
+                <meta itemprop="openingHours" content="Mo-Fr 09:00-17:30">
+            </div>
+            <footer id="hours"><meta itemprop="openingHours" content="Sa 10:00-14:00"></footer>
+        "#;
+        let document = kuchiki::parse_html().one(html);
+        let base_url = get_base_url(&document, Some("https://www.home.dk/kontakt"));
+        let items = get_microdata_items(&document, base_url.as_ref());
+
+        assert_eq!(items.len(), 1);
+        assert_eq!(
+            items[0],
+            serde_json::json!({
+                "type": ["https://schema.org/LocalBusiness"],
+                "properties": {
+                    "name": ["home a/s"],
+                    "logo": ["https://www.home.dk/da/logo.png"],
+                    "image": ["https://www.home.dk/da/logo.png"],
+                    "url": ["https://home.dk/"],
+                    "address": [{
+                        "type": ["https://schema.org/PostalAddress"],
+                        "properties": {
+                            "streetAddress": ["Søren Frichs Vej 36 F"],
+                            "postalCode": ["8230"],
+                            "addressLocality": ["Åbyhøj"],
+                        },
+                    }],
+                    "openingHours": ["Mo-Fr 09:00-17:30", "Sa 10:00-14:00"],
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_get_rdfa() {
+        let html = r##"
+            <div vocab="http://schema.org/" typeof="Organization" resource="#org">
+                <span property="name">home a/s</span>
+                <a property="url" href="/">home</a>
+                <div property="address" typeof="PostalAddress">
+                    <span property="postalCode">8230</span>
+                    <meta property="addressCountry" content="DK">
+                </div>
+                <span property="schema:telephone">86 15 43 00</span>
+            </div>
+        "##;
+        let document = kuchiki::parse_html().one(html);
+        let base_url = get_base_url(&document, Some("https://www.home.dk/kontakt"));
+        let items = get_rdfa_items(&document, base_url.as_ref());
+
+        assert_eq!(
+            items,
+            [serde_json::json!({
+                "type": ["http://schema.org/Organization"],
+                "id": "https://www.home.dk/kontakt#org",
+                "properties": {
+                    "http://schema.org/name": ["home a/s"],
+                    "http://schema.org/url": ["https://www.home.dk/"],
+                    "http://schema.org/address": [{
+                        "type": ["http://schema.org/PostalAddress"],
+                        "properties": {
+                            "http://schema.org/postalCode": ["8230"],
+                            "http://schema.org/addressCountry": ["DK"],
+                        },
+                    }],
+                    "http://schema.org/telephone": ["86 15 43 00"],
+                },
+            })]
+        );
+    }
+
+    #[test]
+    fn test_get_meta() {
+        let html = r#"
+            <head>
+            <title>home a/s</title>
+            <meta http-equiv="Content-Type" content="text/html; charset=utf-8">
+            <meta property="og:title" content="home a/s - ejendomsmægler">
+            <meta property="og:image" content="https://home.dk/1.jpg">
+            <meta property="og:image" content="https://home.dk/2.jpg">
+            <meta property="og:image" content="https://home.dk/1.jpg">
+            <meta name="twitter:card" content="summary">
+            <meta property="article:published_time" content="2024-01-01">
+            <meta property="product:price:amount" content="100">
+            <meta property="fb:app_id" content="1234">
+            <meta name="DC.title" content="home">
+            <meta name="dcterms.language" content="da">
+            <meta name="description" content="meta description">
+            <meta name="robots" content="">
+            </head>
+        "#;
+        let result = get_meta(html.to_string()).unwrap();
+
+        assert_eq!(result.title, "home a/s");
+        assert_eq!(result.og["og:title"], ["home a/s - ejendomsmægler"]);
+        assert_eq!(result.og["og:image"], ["https://home.dk/1.jpg", "https://home.dk/2.jpg"]);
+        assert_eq!(result.twitter["twitter:card"], ["summary"]);
+        assert_eq!(result.article["article:published_time"], ["2024-01-01"]);
+        assert_eq!(result.product["product:price:amount"], ["100"]);
+        assert_eq!(result.fb["fb:app_id"], ["1234"]);
+        assert_eq!(result.dublin_core["dc.title"], ["home"]);
+        assert_eq!(result.dublin_core["dcterms.language"], ["da"]);
+        assert_eq!(result.http_equiv["content-type"], ["text/html; charset=utf-8"]);
+        assert_eq!(result.other["description"], ["meta description"]);
+        assert!(!result.other.contains_key("robots"));
+
+        let titles = get_meta_titles(html.to_string()).unwrap();
+        assert_eq!(titles["og:title"], "home a/s - ejendomsmægler");
+    }
+
+    #[test]
+    fn test_links_resolved_and_classified() {
+        let html = r##"
+            <head>
+            <base href="/da/">
+            <link rel="alternate" hreflang="en" href="../en/">
+            </head>
+            <a href="kontakt">Kontakt</a>
+            <a href="//WWW.Home.dk/om-os/../salg">Salg</a>
+            <a href="https://www.facebook.com/home">Facebook</a>
+            <a href="mailto:homeas@home.dk">Mail</a>
+            <a href="tel:+4586154300">Ring</a>
+            <a href="#top">Top</a>
+            <a href="javascript:void(0)">Menu</a>
+            <a>No href</a>
+        "##;
+        let page_url = Some("https://home.dk/kontakt?x=1");
+
+        let hrefs = get_href_attributes(html.to_string(), page_url).unwrap();
+        assert_eq!(hrefs[0], "https://home.dk/da/kontakt");
+        assert_eq!(hrefs[1], "https://www.home.dk/salg");
+        assert_eq!(hrefs[7], "");
+
+        let links = get_links(html.to_string(), None).unwrap();
+        assert_eq!(links[0], ("kontakt".to_string(), "Kontakt".to_string()));
+
+        let links = get_classified_links(html.to_string(), page_url).unwrap();
+        let kinds: Vec<&str> = links.iter().map(|link| link.kind.as_str()).collect();
+        assert_eq!(
+            kinds,
+            ["internal", "internal", "external", "mailto", "tel", "anchor", "javascript"]
+        );
+        assert_eq!(links[5].url, "https://home.dk/da/#top");
+
+        let alternate = get_alternate_links(html.to_string(), page_url).unwrap();
+        assert_eq!(alternate["en"], ["https://home.dk/en/"]);
+    }
+
+    #[test]
+    fn test_get_phones() {
+        let phones = get_phones(HTML.to_string(), Some("dk".to_string())).unwrap();
+        assert_eq!(
+            phones,
+            [Phone {
+                e164: "+4586154300".to_string(),
+                original: "86 15 43 00".to_string(),
+                source: "text".to_string(),
+                kind: "phone".to_string(),
+                country: Some("DK".to_string()),
+            }]
+        );
+
+        let html = r#"<html lang="sv"><body>
+            <a href="tel:+45%2086%2015%2043%2000">Ring til os</a>
+            <a href="tel:%2B46%20(0)8-123%2045%2000">Ring oss</a>
+            <p>Tel. 08-123 456 78</p>
+            <p>Org.nr 556677-8899, åbent 2024-01-01</p>
+            <p>Call George on +45 70 20 30 40 in the region 2100 just once</p>
+            <p>Fax: 08-123 456 99</p>
+            <script>var id = "0812345678";</script>
+        </body></html>"#;
+        let phones = get_phones(html.to_string(), None).unwrap();
+        let found: Vec<(&str, &str, &str, &str)> = phones
+            .iter()
+            .map(|p| (p.e164.as_str(), p.original.as_str(), p.source.as_str(), p.kind.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("+4586154300", "+45 86 15 43 00", "tel", "phone"),
+                ("+4681234500", "+46 (0)8-123 45 00", "tel", "phone"),
+                ("+46812345678", "08-123 456 78", "text", "phone"),
+                ("+4570203040", "+45 70 20 30 40", "text", "phone"),
+                ("+46812345699", "08-123 456 99", "text", "fax"),
+            ]
+        );
+        assert_eq!(country_from_lang("de-AT"), Some("AT".to_string()));
+        assert_eq!(country_from_lang("en"), None);
+    }
+
+    #[test]
+    fn test_get_addresses() {
+        let addresses = get_addresses(HTML.to_string(), Some("dk".to_string())).unwrap();
+        assert_eq!(
+            addresses,
+            [AddressCandidate {
+                street: Some("Søren Frichs Vej 36 F".to_string()),
+                postcode: Some("8230".to_string()),
+                city: Some("Åbyhøj".to_string()),
+                country: Some("DK".to_string()),
+                confidence: 0.8,
+                source: "text".to_string(),
+                lines: vec!["Søren Frichs Vej 36 F".to_string(), "8230 Åbyhøj".to_string()],
+            }]
+        );
+
+        let html = r#"<html lang="de"><body>
+            <script type="application/ld+json">
+            {"@type": "Organization", "name": "Beispiel GmbH", "address": {"@type": "PostalAddress",
+             "streetAddress": "Hauptstraße 5a", "postalCode": "10115", "addressLocality": "Berlin",
+             "addressCountry": "DE"}}
+            </script>
+            <div itemscope itemtype="https://schema.org/PostalAddress">
+              <span itemprop="streetAddress">Kalverstraat 1</span>
+              <span itemprop="postalCode">1012 NX</span>
+              <span itemprop="addressLocality">Amsterdam</span>
+            </div>
+            <footer>Hauptstraße 5a, 10115 Berlin, Deutschland</footer>
+            <address>10 Downing Street<br>London<br>SW1A 2AA</address>
+            <p>2024 Annual Report</p>
+        </body></html>"#;
+        let addresses = get_addresses(html.to_string(), None).unwrap();
+        let found: Vec<_> = addresses
+            .iter()
+            .map(|a| {
+                (
+                    a.street.as_deref(),
+                    a.postcode.as_deref(),
+                    a.city.as_deref(),
+                    a.country.as_deref(),
+                    a.source.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (Some("Hauptstraße 5a"), Some("10115"), Some("Berlin"), Some("DE"), "json_ld"),
+                (Some("Kalverstraat 1"), Some("1012 NX"), Some("Amsterdam"), None, "microdata"),
+                (Some("10 Downing Street"), Some("SW1A 2AA"), Some("London"), Some("GB"), "text"),
+            ]
+        );
+
+        let text_nodes = vec![vec!["Storgatan 12".to_string(), "SE-114 55 Stockholm".to_string()]];
+        let addresses = get_text_node_addresses(text_nodes, None).unwrap();
+        assert_eq!(addresses[0].country.as_deref(), Some("SE"));
+        assert_eq!(addresses[0].postcode.as_deref(), Some("114 55"));
+
+        let lines = |lines: &[&str]| vec![lines.iter().map(|line| line.to_string()).collect()];
+        let addresses =
+            get_text_node_addresses(lines(&["10 Downing Street", "London", "SW1A 2AA", "UK"]), None)
+                .unwrap();
+        assert_eq!(addresses[0].country.as_deref(), Some("GB"));
+        assert_eq!(addresses[0].confidence, 0.9);
+        // "IT" is a country code, but 8230 isn't an Italian postcode
+        let addresses =
+            get_text_node_addresses(lines(&["Søren Frichs Vej 36 F", "8230 Åbyhøj", "IT"]), None)
+                .unwrap();
+        assert_eq!(addresses[0].country, None);
+
+        // a number and capitalised words on their own aren't an address, even where
+        // the number fits the postcode format of the page's country
+        for lang in [r#" lang="da""#, ""] {
+            let html = format!(
+                "<html{lang}><body><p>2024 Annual Report</p><div><span>150 Happy Customers</span>\
+                 </div><div><span>500 Employees</span></div></body></html>"
+            );
+            let addresses = get_addresses(html, None).unwrap();
+            assert_eq!(addresses, [], "lang: {lang}");
+        }
+    }
+
+    #[test]
+    fn test_get_company_ids() {
+        let ids = get_company_ids(HTML.to_string(), None).unwrap();
+        assert_eq!(
+            ids,
+            [CompanyId {
+                kind: "cvr".to_string(),
+                value: "13394172".to_string(),
+                original: "13394172".to_string(),
+                country: "DK".to_string(),
+                registry: None,
+                validated: true,
+            }]
+        );
+
+        let html = r#"<html lang="de"><body>
+            <footer>
+              <p>Registergericht: Amtsgericht München, HRB 123456</p>
+              <p>USt-IdNr.: DE 136 695 976</p>
+              <p>CVR-nr. 13394173, Moms: DK13394172</p>
+              <p>Acme Ltd. Registered in England and Wales, company number 4567891. VAT no. GB 980 7806 84</p>
+              <p>KvK 12345678, BTW NL123456782B01</p>
+              <p>Org.nr 556036-0793</p>
+              <p>Artikel DE999999999</p>
+            </footer>
+        </body></html>"#;
+        let ids = get_company_ids(html.to_string(), None).unwrap();
+        let found: Vec<_> = ids
+            .iter()
+            .map(|id| (id.kind.as_str(), id.value.as_str(), id.country.as_str(), id.validated))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("org_nr", "5560360793", "SE", true),
+                ("kvk", "12345678", "NL", false),
+                ("company_number", "04567891", "GB", false),
+                ("hrb", "HRB123456", "DE", false),
+                ("vat", "DE136695976", "DE", true),
+                ("vat", "DK13394172", "DK", true),
+                ("vat", "GB980780684", "GB", true),
+                ("vat", "NL123456782B01", "NL", true),
+            ]
+        );
+        assert_eq!(ids[3].registry.as_deref(), Some("München"));
+
+        // labelled, prefixed and well formed, but the check digits are wrong
+        let html = "<p>VAT no. GB 980 7806 85, Moms: DK 13394173, USt-IdNr. DE136695977</p>";
+        assert_eq!(get_company_ids(html.to_string(), None).unwrap(), []);
+
+        // labels are whole words, not part of "customers" or "trust"
+        let html = "<p>Our customers in RO 1234567 and trust ES B1234567C</p>";
+        assert_eq!(get_company_ids(html.to_string(), None).unwrap(), []);
+        let ids = get_company_ids("<p>Cod TVA: RO 1234567</p>".to_string(), None).unwrap();
+        assert_eq!(ids.iter().map(|id| id.value.as_str()).collect::<Vec<_>>(), ["RO1234567"]);
+    }
+
+    #[test]
+    fn test_get_social_profiles() {
+        let html = r#"<html><body>
+            <script type="application/ld+json">
+            {"@type": "Organization", "name": "Acme",
+             "sameAs": ["https://dk.linkedin.com/company/Acme-AS/", "https://twitter.com/AcmeDK"]}
+            </script>
+            <a href="https://www.linkedin.com/company/acme-as">LinkedIn</a>
+            <a href="https://www.linkedin.com/shareArticle?url=https://acme.dk">Share</a>
+            <a href="https://www.facebook.com/sharer/sharer.php?u=https://acme.dk">Share</a>
+            <a href="//m.facebook.com/AcmeDanmark/?ref=footer">Facebook</a>
+            <a href="https://www.facebook.com/pages/Acme-Bakery/123456">Bakery</a>
+            <a href="https://www.facebook.com/pages/Acme-Bakery/789012/">Other bakery</a>
+            <a href="https://twitter.com/intent/tweet?text=hi">Tweet</a>
+            <a href="https://x.com/acmedk">X</a>
+            <a href="https://www.instagram.com/p/Cx1/">Post</a>
+            <a href="https://instagram.com/acme.dk/">Instagram</a>
+            <a href="https://www.youtube.com/watch?v=abc">Video</a>
+            <a href="https://www.youtube.com/@acme/videos">YouTube</a>
+            <a href="https://github.com/acme/widgets">Code</a>
+            <a href="https://www.tiktok.com/@acme.dk?lang=da">TikTok</a>
+            <blockquote><a href="https://twitter.com/someone/status/1712345678">Embedded tweet</a></blockquote>
+            <a href="https://www.facebook.com/otherpage/posts/98765">Shared post</a>
+            <a href="https://www.tiktok.com/@someone/video/7301234567">Video</a>
+            <a href="https://www.facebook.com/pg/acmedanmark/about/">About</a>
+        </body></html>"#;
+        let profiles = get_social_profiles(html.to_string(), None).unwrap();
+        let found: Vec<_> = profiles
+            .iter()
+            .map(|p| (p.platform.as_str(), p.url.as_str(), p.source.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("linkedin", "https://www.linkedin.com/company/acme-as", "json_ld"),
+                ("x", "https://x.com/acmedk", "json_ld"),
+                ("facebook", "https://www.facebook.com/acmedanmark", "link"),
+                ("facebook", "https://www.facebook.com/pages/acme-bakery/123456", "link"),
+                ("facebook", "https://www.facebook.com/pages/acme-bakery/789012", "link"),
+                ("instagram", "https://www.instagram.com/acme.dk", "link"),
+                ("youtube", "https://www.youtube.com/@acme", "link"),
+                ("github", "https://github.com/acme", "link"),
+                ("tiktok", "https://www.tiktok.com/@acme.dk", "link"),
+            ]
+        );
+        assert_eq!(profiles[1].handle, "acmedk");
+        assert_eq!(profiles[3].handle, "123456");
+    }
+
+    #[test]
+    fn test_get_main_content() {
+        let html = r#"<html><body>
+            <header><nav><ul>
+              <li><a href="/">Home</a></li><li><a href="/products">Our products and services</a></li>
+            </ul></nav></header>
+            <div id="cookie-banner"><p>We use cookies to improve your experience, please accept them.</p></div>
+            <div id="page">
+              <aside class="sidebar"><p>Sign up for our newsletter, it is sent out every month.</p></aside>
+              <article class="post">
+                <h1>Annual results</h1>
+                <p>The company grew its revenue by twelve percent, driven by exports to Germany,
+                   Sweden and Norway.</p>
+                <p>Profit before tax was <a href="/report">published in the report</a>, and the board
+                   proposes a dividend, subject to approval at the general meeting.</p>
+              </article>
+            </div>
+            <footer><p>Copyright 2024 Acme A/S, Søren Frichs Vej 36 F, 8230 Åbyhøj</p></footer>
+        </body></html>"#;
+        let content = get_main_content(html.to_string()).unwrap().unwrap();
+        assert_eq!(content.path, "html > body > div#page > article");
+        assert_eq!(
+            content.text,
+            "Annual results\n\n\
+             The company grew its revenue by twelve percent, driven by exports to Germany, Sweden and Norway.\n\n\
+             Profit before tax was published in the report, and the board proposes a dividend, \
+             subject to approval at the general meeting."
+        );
+        assert!(content.link_density > 0.0 && content.link_density < 0.2);
+
+        assert!(get_main_content("<html><body><a href=\"/\">Home</a></body></html>".to_string())
+            .unwrap()
+            .is_none());
+
+        // ids that aren't CSS identifiers fall back to positions, and the scripts stay put
+        let document = kuchiki::parse_html().one(
+            r#"<html><body><div id="1col"></div><div id="a.b"><article>
+              <p>The company grew its revenue by twelve percent, driven by exports.</p>
+              <script>var tracking = "a long string that is not part of the article";</script>
+            </article></div></body></html>"#,
+        );
+        let content = find_main_content(&document).unwrap();
+        assert_eq!(content.path, "html > body > div:nth-of-type(2) > article");
+        assert_eq!(content.text, "The company grew its revenue by twelve percent, driven by exports.");
+        assert!(document.select_first("script").is_ok());
+    }
+
+    #[test]
+    fn test_html_to_markdown() {
+        let html = r#"<html><head><title>Ignored</title></head><body>
+            <div class="cookies">We use cookies</div>
+            <h1>Home  <small>A/S</small></h1>
+            <p>We sell <strong>solar panels</strong> and <a href="/batteries">home batteries</a>.<br>
+               Call us <em>today</em>.</p>
+            <ul>
+              <li>Panels <ul><li>Mono</li><li>Poly</li></ul></li>
+              <li><a href="https://example.com/x">Inverters</a></li>
+            </ul>
+            <ol start="3"><li>Third</li></ol>
+            <img src="/logo.png" alt="Home logo">
+            <table><tr><th>Day</th><th>Hours</th></tr><tr><td>Mon</td><td>8-16 | 17-20</td></tr></table>
+            <blockquote><p>Great service</p></blockquote>
+            <pre>let x = 1;
+let y = 2;</pre>
+            <script>var x = 1;</script>
+        </body></html>"#;
+        let page_url = Some("https://home.dk/");
+        let markdown = html_to_markdown(html.to_string(), "inline", "markdown", page_url).unwrap();
+        assert_eq!(
+            markdown,
+            "# Home A/S\n\n\
+             We sell **solar panels** and [home batteries](https://home.dk/batteries).\n\
+             Call us *today*.\n\n\
+             - Panels\n  - Mono\n  - Poly\n- [Inverters](https://example.com/x)\n\n\
+             3. Third\n\n\
+             ![Home logo](https://home.dk/logo.png)\n\n\
+             | Day | Hours |\n| --- | --- |\n| Mon | 8-16 \\| 17-20 |\n\n\
+             > Great service\n\n\
+             ```\nlet x = 1;\nlet y = 2;\n```"
+        );
+
+        let html = r#"<p><a href="/a">A</a>, <a href="/b">B</a> and <a href="/a">A again</a>
+            <img src="/x.png" alt="X"></p>"#;
+        let markdown = html_to_markdown(html.to_string(), "reference", "none", None).unwrap();
+        assert_eq!(markdown, "[A][1], [B][2] and [A again][1]\n\n[1]: /a\n[2]: /b");
+        let markdown = html_to_markdown(html.to_string(), "text", "alt", None).unwrap();
+        assert_eq!(markdown, "A, B and A again X");
+        assert!(html_to_markdown(html.to_string(), "footnote", "alt", None).is_err());
+    }
+
+    #[test]
+    fn test_get_tables() {
+        let html = r#"<html><body>
+            <table role="presentation" id="layout"><tr><td>
+              <table class="hours">
+                <caption>Opening hours</caption>
+                <thead>
+                  <tr><th rowspan="2">Day</th><th colspan="2">Shop</th></tr>
+                  <tr><th>Opens</th><th>Closes</th></tr>
+                </thead>
+                <tbody>
+                  <tr><td>Mon</td><td>8:00</td><td rowspan="2">17:00</td></tr>
+                  <tr><td>Tue</td><td>9:00</td></tr>
+                  <tr><td>Sat</td><td colspan="2">Closed</td></tr>
+                </tbody>
+              </table>
+              <p>Visit us</p>
+            </td></tr></table>
+            <table><tr><td>Name</td><td>Role</td></tr><tr><td>Anna</td><td>CEO</td></tr></table>
+        </body></html>"#;
+        let tables = get_tables(html.to_string()).unwrap();
+        assert_eq!(tables.len(), 3);
+
+        assert!(tables[0].is_layout);
+        assert_eq!(tables[0].rows, [["Visit us"]]);
+        assert_eq!(tables[0].path, "html > body > table#layout");
+
+        let hours = &tables[1];
+        assert_eq!(hours.parent, Some(0));
+        assert_eq!(hours.caption.as_deref(), Some("Opening hours"));
+        assert_eq!(hours.headers, ["Day", "Shop / Opens", "Shop / Closes"]);
+        assert_eq!(
+            hours.rows,
+            [
+                ["Mon", "8:00", "17:00"],
+                ["Tue", "9:00", "17:00"],
+                ["Sat", "Closed", "Closed"],
+            ]
+        );
+        assert!(!hours.is_layout);
+
+        assert!(tables[2].headers.is_empty());
+        assert_eq!(tables[2].rows, [["Name", "Role"], ["Anna", "CEO"]]);
+        assert!(!tables[2].is_layout);
+        assert_eq!(tables[2].parent, None);
+    }
+
+    #[test]
+    fn test_removal_rules() {
+        let html = r#"<html><body>
+            <div class="cookies"><p>We track visits to this site</p></div>
+            <div class="promo"><p>Buy now and save</p></div>
+            <div class="promo legal"><p>Prices include VAT</p></div>
+            <h2>Products</h2><h4>Small print</h4>
+        </body></html>"#;
+        let sentences = |rules: Option<RulesArg>| {
+            get_sentences(html.to_string(), "_stop_", false, false, false, None, rules).unwrap()
+        };
+
+        let result = sentences(None);
+        assert_eq!(result.p, ["Buy now and save", "Prices include VAT"]);
+        assert_eq!(result.h["h4"], ["Small print"]);
+
+        let rules = RemovalRules::new(
+            vec![".promo".to_string()],
+            vec![".legal".to_string()],
+            Some(vec!["h2".to_string()]),
+            false,
+            None,
+            None,
+        )
+        .unwrap();
+        let result = sentences(Some(RulesArg::Rules(rules.clone())));
+        assert_eq!(result.p, ["We track visits to this site", "Prices include VAT"]);
+        assert_eq!(result.h.keys().collect::<Vec<_>>(), ["h2"]);
+
+        register_removal_rules("shop".to_string(), rules);
+        assert!(removal_rule_names().contains(&"shop".to_string()));
+        let results = get_sentences_parallel(
+            vec![html.to_string(), html.to_string()],
+            "_stop_",
+            false,
+            false,
+            false,
+            None,
+            Some(RulesArg::Name("shop".to_string())),
+        )
+        .unwrap();
+        assert!(results.iter().all(|result| result.p.len() == 2));
+        assert!(unregister_removal_rules("shop"));
+        assert!(resolve_rules(Some(RulesArg::Name("shop".to_string()))).is_err());
+
+        assert!(RemovalRules::new(vec!["div[".to_string()], vec![], None, true, None, None).is_err());
+    }
+
+    #[test]
+    fn test_consent_banners() {
+        let html = r#"<html><head>
+            <script src="https://cdn.cookielaw.org/scripttemplates/otSDKStub.js"></script>
+        </head><body>
+            <p>Our bakery sells chocolate chip cookies every morning</p>
+            <div id="onetrust-consent-sdk"><div id="onetrust-banner-sdk">
+              <p>We value your privacy and use tracking technologies</p>
+              <span>Accept all and continue browsing</span>
+            </div></div>
+        </body></html>"#;
+        let sentences = |html: &str, rules: Option<RulesArg>| {
+            get_sentences(html.to_string(), "_stop_", false, false, true, None, rules).unwrap()
+        };
+
+        let result = sentences(html, None);
+        assert_eq!(result.consent_vendor.as_deref(), Some("OneTrust"));
+        assert_eq!(result.p, ["Our bakery sells chocolate chip cookies every morning"]);
+        assert_eq!(result.text_nodes, [vec!["Our bakery sells chocolate chip cookies every morning"]]);
+        assert!(result.other.iter().all(|text| !text.contains("Accept all")));
+
+        let rules = RemovalRules::new(vec![], vec![], None, false, None, None).unwrap();
+        let result = sentences(html, Some(RulesArg::Rules(rules)));
+        assert_eq!(result.consent_vendor.as_deref(), Some("OneTrust"));
+        assert_eq!(result.p.len(), 2);
+
+        // loaded by script, the banner isn't in the served HTML
+        let html = r#"<html><head>
+            <script id="Cookiebot" src="https://consent.cookiebot.com/uc.js" data-cbid="x"></script>
+        </head><body><p>Nothing to see here today</p></body></html>"#;
+        assert_eq!(sentences(html, None).consent_vendor.as_deref(), Some("Cookiebot"));
+        assert_eq!(sentences("<p>No banner on this page</p>", None).consent_vendor, None);
+
+        // no known consent manager, only a banner the site built itself
+        let html = r#"<html><body class="cookie-banner">
+            <div id="cookie-banner"><p>We use cookies to improve your experience</p></div>
+            <div class="fixed cookie-consent"><p>Accept all cookies on this site</p></div>
+            <p>Read how our cookies recipe won a prize</p>
+        </body></html>"#;
+        let result = sentences(html, None);
+        assert_eq!(result.consent_vendor, None);
+        assert_eq!(result.p, ["Read how our cookies recipe won a prize"]);
+        assert_eq!(result.text_nodes, [vec!["Read how our cookies recipe won a prize"]]);
+
+        // wrappers flagged for the banner's offset hold the page, not the banner
+        let html = r#"<html><body>
+            <div id="page" class="has-cookie-banner">
+              <main class="with-cookie-bar-offset"><p>Our bakery opens at seven every day</p></main>
+            </div>
+        </body></html>"#;
+        assert_eq!(sentences(html, None).p, ["Our bakery opens at seven every day"]);
+    }
+
     #[test]
     fn test_get_emails() {
         let html = "\
@@ -452,22 +1460,91 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_get_email_addresses() {
+        let html = r#"<html><body>
+            <p>Skriv til <a href="mailto:Shop@RespectResources.dk?subject=Hej">shop@respectresources.dk</a>
+            eller shop@respectresources.dk</p>
+            <p>Sales: sales [at] acme [dot] com, support(at)acme.com</p>
+            <p>Press: press at acme dot com. Meet us at Main. Street</p>
+            <p>Contact us at acme dot com, or see you at expo dot dk</p>
+            <p>&#105;&#110;&#102;&#111;&#64;&#97;&#99;&#109;&#101;&#46;&#100;&#107;</p>
+            <a href="/cdn-cgi/l/email-protection#6a09020b071a2a0b09070f440e01">[email&#160;protected]</a>
+            <span class="__cf_email__" data-cfemail="137a7d757c53727e7a6b3d7778">[email&#160;protected]</span>
+            <script>var e = 'jobs' + '@' + 'acme.com'; document.write(e);</script>
+            <button data-email="hr@acme.com" onclick="location.href='mailto:' + 'ceo' + '@' + 'acme.com'">Mail</button>
+        </body></html>"#;
+        let emails = get_email_addresses(html.to_string()).unwrap();
+        let found: Vec<_> = emails
+            .iter()
+            .map(|e| (e.address.as_str(), e.source.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("Shop@respectresources.dk", "mailto"),
+                ("champ@acme.dk", "attribute"),
+                ("info@amix.dk", "attribute"),
+                ("hr@acme.com", "attribute"),
+                ("ceo@acme.com", "attribute"),
+                ("sales@acme.com", "text"),
+                ("support@acme.com", "text"),
+                ("press@acme.com", "text"),
+                ("info@acme.dk", "text"),
+                ("jobs@acme.com", "script"),
+            ]
+        );
+    }
 }
 
 /// A Python module implemented in Rust.
 #[pymodule]
 fn html_parsing_tools(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(get_emails, m)?)?;
+    m.add_function(wrap_pyfunction!(get_email_addresses, m)?)?;
+    m.add_function(wrap_pyfunction!(get_phones, m)?)?;
+    m.add_function(wrap_pyfunction!(get_addresses, m)?)?;
+    m.add_function(wrap_pyfunction!(get_company_ids, m)?)?;
+    m.add_function(wrap_pyfunction!(get_text_node_addresses, m)?)?;
     m.add_function(wrap_pyfunction!(get_links, m)?)?;
+    m.add_function(wrap_pyfunction!(get_classified_links, m)?)?;
+    m.add_function(wrap_pyfunction!(get_social_profiles, m)?)?;
     m.add_function(wrap_pyfunction!(html_contents, m)?)?;
+    m.add_function(wrap_pyfunction!(html_to_markdown, m)?)?;
     m.add_function(wrap_pyfunction!(tag_html_contents, m)?)?;
     m.add_function(wrap_pyfunction!(tag_attribute, m)?)?;
     m.add_function(wrap_pyfunction!(get_sentences, m)?)?;
     m.add_function(wrap_pyfunction!(get_sentences_parallel, m)?)?;
+    m.add_function(wrap_pyfunction!(register_removal_rules, m)?)?;
+    m.add_function(wrap_pyfunction!(unregister_removal_rules, m)?)?;
+    m.add_function(wrap_pyfunction!(removal_rule_names, m)?)?;
+    m.add_function(wrap_pyfunction!(get_main_content, m)?)?;
+    m.add_function(wrap_pyfunction!(get_tables, m)?)?;
     m.add_function(wrap_pyfunction!(get_href_attributes, m)?)?;
     m.add_function(wrap_pyfunction!(get_alternate_links, m)?)?;
     m.add_function(wrap_pyfunction!(get_lang, m)?)?;
     m.add_function(wrap_pyfunction!(get_meta_titles, m)?)?;
+    m.add_function(wrap_pyfunction!(get_meta, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_json_ld, m)?)?;
+    m.add_function(wrap_pyfunction!(get_microdata, m)?)?;
+    m.add_function(wrap_pyfunction!(get_rdfa, m)?)?;
     m.add_class::<GetSentencesResult>()?;
+    m.add_class::<RemovalRules>()?;
+    m.add_class::<GetMetaResult>()?;
+    m.add_class::<MainContent>()?;
+    m.add_class::<Table>()?;
+    m.add_class::<Link>()?;
+    m.add_class::<SocialProfile>()?;
+    m.add_class::<Email>()?;
+    m.add_class::<Phone>()?;
+    m.add_class::<AddressCandidate>()?;
+    m.add_class::<CompanyId>()?;
+    m.add_class::<JsonLd>()?;
+    m.add_class::<Organization>()?;
+    m.add_class::<LocalBusiness>()?;
+    m.add_class::<Product>()?;
+    m.add_class::<Person>()?;
+    m.add_class::<PostalAddress>()?;
     Ok(())
 }
@@ -0,0 +1,313 @@
+use crate::utils::resolve_url;
+use kuchiki::NodeRef;
+use url::Url;
+
+const INLINE_TAGS: [&str; 31] = [
+    "a", "abbr", "b", "bdi", "bdo", "br", "cite", "code", "data", "del", "dfn", "em", "font",
+    "i", "img", "ins", "kbd", "label", "mark", "q", "s", "samp", "small", "span", "strike",
+    "strong", "sub", "sup", "time", "u", "var",
+];
+// Form controls and media that have no text worth keeping
+const SKIP_TAGS: [&str; 10] = [
+    "head", "input", "select", "textarea", "button", "iframe", "video", "audio", "svg", "canvas",
+];
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum LinkStyle {
+    /// `[text](url)`
+    Inline,
+    /// `[text][1]` with the URLs listed at the end
+    Reference,
+    /// the link text only
+    Text,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ImageStyle {
+    /// `![alt](src)`
+    Markdown,
+    /// the alt text only
+    Alt,
+    None,
+}
+
+pub struct MarkdownWriter {
+    links: LinkStyle,
+    images: ImageStyle,
+    base_url: Option<Url>,
+    references: Vec<String>,
+}
+
+fn tag_name(node: &NodeRef) -> Option<String> {
+    node.as_element().map(|element| element.name.local.to_string())
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut last_space = false;
+    for c in text.chars() {
+        if c.is_whitespace() && c != '\u{a0}' {
+            if !last_space {
+                result.push(' ');
+            }
+            last_space = true;
+        } else {
+            result.push(c);
+            last_space = false;
+        }
+    }
+    result
+}
+
+// Trims every line and drops the spaces left around inline line breaks
+fn tidy(text: &str) -> String {
+    text.split('\n')
+        .map(|line| line.trim())
+        .collect::<Vec<&str>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+// `**`, `*` and `` ` `` around the trimmed text, with the surrounding spaces kept outside
+fn wrap(text: &str, marker: &str) -> String {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return text.to_string();
+    }
+    let leading = if text.starts_with(' ') { " " } else { "" };
+    let trailing = if text.ends_with(' ') { " " } else { "" };
+    format!("{leading}{marker}{trimmed}{marker}{trailing}")
+}
+
+impl MarkdownWriter {
+    pub fn new(links: LinkStyle, images: ImageStyle, base_url: Option<Url>) -> Self {
+        MarkdownWriter {
+            links,
+            images,
+            base_url,
+            references: vec![],
+        }
+    }
+
+    pub fn convert(mut self, node: &NodeRef) -> String {
+        let mut blocks: Vec<String> = vec![];
+        self.blocks(node, &mut blocks);
+        let mut markdown = blocks.join("\n\n");
+        if !self.references.is_empty() {
+            let references: Vec<String> = self
+                .references
+                .iter()
+                .enumerate()
+                .map(|(ix, url)| format!("[{}]: {url}", ix + 1))
+                .collect();
+            markdown.push_str("\n\n");
+            markdown.push_str(&references.join("\n"));
+        }
+        markdown
+    }
+
+    fn inline(&mut self, node: &NodeRef) -> String {
+        if let Some(text) = node.as_text() {
+            return collapse_whitespace(&text.borrow());
+        }
+        let Some(tag) = tag_name(node) else {
+            return String::new();
+        };
+        if SKIP_TAGS.contains(&tag.as_str()) {
+            return String::new();
+        }
+        let children = |writer: &mut Self| -> String {
+            node.children().map(|child| writer.inline(&child)).collect()
+        };
+        match tag.as_str() {
+            "br" => "\n".to_string(),
+            "strong" | "b" => wrap(&children(self), "**"),
+            "em" | "i" => wrap(&children(self), "*"),
+            "del" | "s" | "strike" => wrap(&children(self), "~~"),
+            "code" | "kbd" | "samp" => wrap(&collapse_whitespace(&node.text_contents()), "`"),
+            "img" => self.image(node),
+            "a" => {
+                let text = children(self);
+                self.link(node, text)
+            }
+            _ => children(self),
+        }
+    }
+
+    fn image(&self, node: &NodeRef) -> String {
+        let element = node.as_element().unwrap();
+        let attributes = element.attributes.borrow();
+        let alt = collapse_whitespace(attributes.get("alt").unwrap_or("")).trim().to_string();
+        match self.images {
+            ImageStyle::None => String::new(),
+            ImageStyle::Alt => alt,
+            ImageStyle::Markdown => match attributes.get("src") {
+                Some(src) => format!("![{alt}]({})", resolve_url(self.base_url.as_ref(), src)),
+                None => alt,
+            },
+        }
+    }
+
+    fn link(&mut self, node: &NodeRef, text: String) -> String {
+        let element = node.as_element().unwrap();
+        let href = element.attributes.borrow().get("href").unwrap_or("").trim().to_string();
+        let label = text.trim();
+        // anchors and script links have nowhere to go outside the page
+        let no_target = href.is_empty() || href.starts_with('#') || href.starts_with("javascript:");
+        if label.is_empty() || no_target {
+            return text;
+        }
+        let url = resolve_url(self.base_url.as_ref(), &href);
+        let leading = if text.starts_with(' ') { " " } else { "" };
+        let trailing = if text.ends_with(' ') { " " } else { "" };
+        let link = match self.links {
+            LinkStyle::Text => return text,
+            LinkStyle::Inline => format!("[{label}]({url})"),
+            LinkStyle::Reference => {
+                let number = match self.references.iter().position(|known| *known == url) {
+                    Some(ix) => ix + 1,
+                    None => {
+                        self.references.push(url);
+                        self.references.len()
+                    }
+                };
+                format!("[{label}][{number}]")
+            }
+        };
+        format!("{leading}{link}{trailing}")
+    }
+
+    fn blocks(&mut self, node: &NodeRef, out: &mut Vec<String>) {
+        let mut paragraph = String::new();
+        let flush = |paragraph: &mut String, out: &mut Vec<String>| {
+            let text = tidy(paragraph);
+            if !text.is_empty() {
+                out.push(text);
+            }
+            paragraph.clear();
+        };
+        for child in node.children() {
+            let tag = tag_name(&child);
+            match tag.as_deref() {
+                None => paragraph.push_str(&self.inline(&child)),
+                Some(tag) if INLINE_TAGS.contains(&tag) => {
+                    paragraph.push_str(&self.inline(&child))
+                }
+                Some(tag) if SKIP_TAGS.contains(&tag) => {}
+                Some(tag) => {
+                    flush(&mut paragraph, out);
+                    self.block(&child, tag, out);
+                }
+            }
+        }
+        flush(&mut paragraph, out);
+    }
+
+    fn block(&mut self, node: &NodeRef, tag: &str, out: &mut Vec<String>) {
+        let text = match tag {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = tag[1..].parse::<usize>().unwrap_or(1);
+                let heading = self.inline(node).replace('\n', " ");
+                let heading = heading.trim();
+                if heading.is_empty() {
+                    return;
+                }
+                format!("{} {heading}", "#".repeat(level))
+            }
+            "ul" | "ol" => self.list(node, tag == "ol"),
+            "pre" => format!("```\n{}\n```", node.text_contents().trim_end()),
+            "blockquote" => {
+                let mut inner: Vec<String> = vec![];
+                self.blocks(node, &mut inner);
+                inner
+                    .join("\n\n")
+                    .lines()
+                    .map(|line| match line {
+                        "" => ">".to_string(),
+                        _ => format!("> {line}"),
+                    })
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            }
+            "table" => self.table(node),
+            "hr" => "---".to_string(),
+            _ => return self.blocks(node, out),
+        };
+        if !text.trim().is_empty() {
+            out.push(text);
+        }
+    }
+
+    fn list(&mut self, node: &NodeRef, ordered: bool) -> String {
+        let start = node
+            .as_element()
+            .and_then(|element| element.attributes.borrow().get("start")?.parse::<usize>().ok())
+            .unwrap_or(1);
+        let mut items: Vec<String> = vec![];
+        for item in node.children().filter(|child| tag_name(child).as_deref() == Some("li")) {
+            let mut inner: Vec<String> = vec![];
+            self.blocks(&item, &mut inner);
+            if inner.is_empty() {
+                continue;
+            }
+            let marker = match ordered {
+                true => format!("{}. ", start + items.len()),
+                false => "- ".to_string(),
+            };
+            let indent = " ".repeat(marker.len());
+            let lines: Vec<String> = inner
+                .join("\n")
+                .lines()
+                .enumerate()
+                .map(|(ix, line)| match ix {
+                    0 => format!("{marker}{line}"),
+                    _ if line.is_empty() => String::new(),
+                    _ => format!("{indent}{line}"),
+                })
+                .collect();
+            items.push(lines.join("\n"));
+        }
+        items.join("\n")
+    }
+
+    // Rows of this table only; nested tables end up flattened into their cell
+    fn table(&mut self, node: &NodeRef) -> String {
+        let mut rows: Vec<Vec<String>> = vec![];
+        let sections = node.children().filter(|child| {
+            matches!(tag_name(child).as_deref(), Some("thead" | "tbody" | "tfoot"))
+        });
+        let row_nodes: Vec<NodeRef> = node
+            .children()
+            .chain(sections.flat_map(|section| section.children()))
+            .filter(|child| tag_name(child).as_deref() == Some("tr"))
+            .collect();
+        for row in row_nodes {
+            let cells: Vec<String> = row
+                .children()
+                .filter(|cell| matches!(tag_name(cell).as_deref(), Some("td" | "th")))
+                .map(|cell| {
+                    let mut inner: Vec<String> = vec![];
+                    self.blocks(&cell, &mut inner);
+                    inner.join(" ").replace('\n', " ").replace('|', "\\|")
+                })
+                .collect();
+            if !cells.is_empty() {
+                rows.push(cells);
+            }
+        }
+        let columns = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+        if columns == 0 {
+            return String::new();
+        }
+        let line = |cells: &[String]| -> String {
+            let padded: Vec<&str> = (0..columns)
+                .map(|ix| cells.get(ix).map_or("", |cell| cell.as_str()))
+                .collect();
+            format!("| {} |", padded.join(" | "))
+        };
+        let mut lines = vec![line(&rows[0]), format!("|{}", " --- |".repeat(columns))];
+        lines.extend(rows[1..].iter().map(|row| line(row)));
+        lines.join("\n")
+    }
+}
@@ -0,0 +1,162 @@
+use kuchiki::iter::NodeIterator;
+use kuchiki::NodeRef;
+use lazy_static::lazy_static;
+use percent_encoding::percent_decode_str;
+use phonenumber::{country, Mode, PhoneNumber};
+use pyo3::prelude::*;
+use regex::Regex;
+use std::collections::HashSet;
+
+lazy_static! {
+    static ref PHONE_CANDIDATE_RE: Regex =
+        Regex::new(r"\+?\(?\d[\d\s().\-/]{4,18}\d\b").expect("Invalid Regex");
+    static ref DATE_RE: Regex =
+        Regex::new(r"^(?:\d{4}[./-]\d{1,2}[./-]\d{1,2}|\d{1,2}[./-]\d{1,2}[./-]\d{2,4})$")
+            .expect("Invalid Regex");
+    // Labels just before a number that make it something other than a contact phone, as
+    // whole words so "George" and "region" don't count as "org" and "reg"
+    static ref NOT_PHONE_LABEL_RE: Regex = Regex::new(
+        r"\b(?:cvr|vat|moms|org|reg|se-nr|se nr|p-nr|konto|iban|bic|swift|kvk|btw|ust|hrb|siret|siren|postbox)\b"
+    )
+    .expect("Invalid Regex");
+    static ref FAX_LABEL_RE: Regex = Regex::new(r"\b(?:fax|telefax)\b").expect("Invalid Regex");
+}
+
+// Language subtag to country, for html lang without a region
+const LANGUAGE_COUNTRIES: [(&str, &str); 24] = [
+    ("da", "DK"),
+    ("sv", "SE"),
+    ("nb", "NO"),
+    ("nn", "NO"),
+    ("no", "NO"),
+    ("fi", "FI"),
+    ("is", "IS"),
+    ("de", "DE"),
+    ("nl", "NL"),
+    ("fr", "FR"),
+    ("es", "ES"),
+    ("it", "IT"),
+    ("pt", "PT"),
+    ("pl", "PL"),
+    ("cs", "CZ"),
+    ("sk", "SK"),
+    ("hu", "HU"),
+    ("et", "EE"),
+    ("lv", "LV"),
+    ("lt", "LT"),
+    ("el", "GR"),
+    ("sl", "SI"),
+    ("hr", "HR"),
+    ("ro", "RO"),
+];
+
+#[pyclass(get_all, module = "html_parsing_tools")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Phone {
+    pub e164: String,
+    /// the number as written on the page
+    pub original: String,
+    /// "tel" for tel: links, "text" for visible text
+    pub source: String,
+    /// "fax" when the number is labelled as one, otherwise "phone"
+    pub kind: String,
+    /// ISO 3166 alpha-2 country of the number, when known
+    pub country: Option<String>,
+}
+
+// "da-DK" gives DK, "da" gives DK, "en" gives nothing
+pub fn country_from_lang(lang: &str) -> Option<String> {
+    let mut parts = lang.trim().split(['-', '_']);
+    let language = parts.next()?.to_lowercase();
+    if let Some(region) = parts.next().filter(|region| region.len() == 2) {
+        return Some(region.to_uppercase());
+    }
+    LANGUAGE_COUNTRIES
+        .iter()
+        .find(|(code, _)| *code == language)
+        .map(|(_, country)| country.to_string())
+}
+
+fn parse_phone(text: &str, country_hint: Option<country::Id>) -> Option<PhoneNumber> {
+    let digits = text.chars().filter(|c| c.is_ascii_digit()).count();
+    if !(6..=15).contains(&digits) {
+        return None;
+    }
+    let number = phonenumber::parse(country_hint, text).ok()?;
+    phonenumber::is_valid(&number).then_some(number)
+}
+
+fn to_phone(number: &PhoneNumber, original: &str, source: &str, kind: &str) -> Phone {
+    Phone {
+        e164: number.format().mode(Mode::E164).to_string(),
+        original: original.to_string(),
+        source: source.to_string(),
+        kind: kind.to_string(),
+        country: number.country().id().map(|id| id.as_ref().to_string()),
+    }
+}
+
+pub fn get_phone_numbers(document: &NodeRef, country_hint: Option<&str>) -> Vec<Phone> {
+    let country_hint: Option<country::Id> =
+        country_hint.and_then(|country| country.to_uppercase().parse().ok());
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut result: Vec<Phone> = vec![];
+
+    for link in document.select("a[href]").unwrap() {
+        let attributes = link.attributes.borrow();
+        let href = attributes.get("href").unwrap_or("").trim();
+        let Some(number) = href
+            .strip_prefix("tel:")
+            .or_else(|| href.strip_prefix("callto:"))
+        else {
+            continue;
+        };
+        let original = percent_decode_str(number).decode_utf8_lossy();
+        if let Some(number) = parse_phone(&original, country_hint) {
+            let phone = to_phone(&number, &original, "tel", "phone");
+            if seen.insert(phone.e164.clone()) {
+                result.push(phone);
+            }
+        }
+    }
+
+    for text_node in document.inclusive_descendants().text_nodes() {
+        let parent_is_code = text_node
+            .as_node()
+            .parent()
+            .and_then(|parent| parent.as_element().map(|e| e.name.local.to_string()))
+            .is_some_and(|tag| ["script", "style", "noscript"].contains(&tag.as_str()));
+        if parent_is_code {
+            continue;
+        }
+        let text = text_node.borrow().to_string();
+        for candidate in PHONE_CANDIDATE_RE.find_iter(&text) {
+            let preceding = &text[..candidate.start()];
+            let start = preceding.char_indices().rev().nth(11).map_or(0, |(ix, _)| ix);
+            // widen to the start of the word the 12 characters cut into
+            let start = preceding[..start]
+                .char_indices()
+                .rev()
+                .take_while(|(_, c)| c.is_alphanumeric())
+                .last()
+                .map_or(start, |(ix, _)| ix);
+            let before = preceding[start..].to_lowercase();
+            if NOT_PHONE_LABEL_RE.is_match(&before) {
+                continue;
+            }
+            let original = candidate.as_str().trim();
+            if DATE_RE.is_match(original) {
+                continue;
+            }
+            if let Some(number) = parse_phone(original, country_hint) {
+                let kind = if FAX_LABEL_RE.is_match(&before) { "fax" } else { "phone" };
+                let phone = to_phone(&number, original, "text", kind);
+                if seen.insert(phone.e164.clone()) {
+                    result.push(phone);
+                }
+            }
+        }
+    }
+
+    result
+}
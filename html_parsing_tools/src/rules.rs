@@ -0,0 +1,230 @@
+use crate::consent::consent_containers;
+use kuchiki::{NodeRef, Selectors};
+use lazy_static::lazy_static;
+use pyo3::exceptions::{PyKeyError, PyValueError};
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+pub const REMOVE_TAGS: [&str; 6] = [
+    // scripts/styles
+    "script",
+    "style",
+    "noscript",
+    // testimonials
+    ".testimonial",
+    ".testimonial-text",
+    ".pwr-testimonial__quote", // hubspot
+];
+
+pub const CONSENT_BANNER_TAGS: [&str; 21] = [
+    "#coiOverlay",
+    ".CookiesOK",
+    "#closeCookieBanner",
+    ".CookieBanner-button",
+    "#nts-set-cookie",
+    ".cc_btn_accept_all",
+    ".cookies",
+    ".noticeCookiesContent .CustomDismissCtrl",
+    ".cookie-consent .cookie-btn",
+    "#accept-cookies",
+    "#cookie_button_agree",
+    "#cookies-agreement #agree-button",
+    "#cookielayer .action-btn",
+    ".cookie.nag .close",
+    "#__tealiumGDPRecModal #consent_prompt_submit",
+    ".gdpr__button",
+    ".eu-cookie-compliance-agree-button",
+    ".cookie-notification .js-cookie-notification-hide",
+    ".js-accept-cookie-policy",
+    "#moove_gdpr_cookie_info_bar",
+    ".pea_cook_wrapper",
+];
+
+// Banners of sites without a known consent manager, removed as a whole. Only whole class
+// names and ids, since wrappers like "has-cookie-banner" hold the page itself, and only
+// below body, which some pages mark with the same class while the banner is open
+pub const CONSENT_CONTAINER_TAGS: [&str; 10] = [
+    "body #cookie-banner",
+    "body .cookie-banner",
+    "body #cookie-consent",
+    "body .cookie-consent",
+    "body #cookie-notice",
+    "body .cookie-notice",
+    "body #cookie-bar",
+    "body .cookie-bar",
+    "body #consent-banner",
+    "body .consent-banner",
+];
+
+pub const PICK_TAGS: [&str; 6] = ["h1", "h2", "h3", "h4", "h5", "h6"];
+
+const HEADER_TAGS: [&str; 4] = ["header", "nav", ".header", ".header-hero"];
+
+const FOOTER_TAGS: [&str; 3] = ["footer", ".footer", ".footer-hero"];
+
+lazy_static! {
+    // Rule sets registered from Python, shared by every thread of get_sentences_parallel
+    static ref RULE_SETS: RwLock<HashMap<String, RemovalRules>> = RwLock::new(HashMap::new());
+}
+
+/// What `get_sentences` removes before reading the page, and which tags it picks.
+#[pyclass(module = "html_parsing_tools", from_py_object)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct RemovalRules {
+    /// selectors removed on top of the built-in ones
+    #[pyo3(get)]
+    pub remove: Vec<String>,
+    /// selectors never removed, nor anything containing them
+    #[pyo3(get)]
+    pub keep: Vec<String>,
+    /// tags whose text goes into `h`
+    #[pyo3(get)]
+    pub pick_tags: Vec<String>,
+    /// remove the containers of known consent managers and the built-in cookie banner selectors
+    #[pyo3(get)]
+    pub consent_banners: bool,
+    /// removed with `remove_header=True`
+    #[pyo3(get)]
+    pub header: Vec<String>,
+    /// removed with `remove_footer=True`
+    #[pyo3(get)]
+    pub footer: Vec<String>,
+}
+
+fn to_strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
+
+fn validate_selectors(selectors: &[String]) -> PyResult<()> {
+    for selector in selectors {
+        if Selectors::compile(selector).is_err() {
+            return Err(PyValueError::new_err(format!("invalid selector: {selector}")));
+        }
+    }
+    Ok(())
+}
+
+impl Default for RemovalRules {
+    fn default() -> Self {
+        RemovalRules {
+            remove: vec![],
+            keep: vec![],
+            pick_tags: to_strings(&PICK_TAGS),
+            consent_banners: true,
+            header: to_strings(&HEADER_TAGS),
+            footer: to_strings(&FOOTER_TAGS),
+        }
+    }
+}
+
+#[pymethods]
+impl RemovalRules {
+    #[new]
+    #[pyo3(signature = (*, remove=vec![], keep=vec![], pick_tags=None, consent_banners=true, header=None, footer=None))]
+    pub fn new(
+        remove: Vec<String>,
+        keep: Vec<String>,
+        pick_tags: Option<Vec<String>>,
+        consent_banners: bool,
+        header: Option<Vec<String>>,
+        footer: Option<Vec<String>>,
+    ) -> PyResult<Self> {
+        let defaults = RemovalRules::default();
+        let rules = RemovalRules {
+            remove,
+            keep,
+            pick_tags: pick_tags.unwrap_or(defaults.pick_tags),
+            consent_banners,
+            header: header.unwrap_or(defaults.header),
+            footer: footer.unwrap_or(defaults.footer),
+        };
+        for selectors in [&rules.remove, &rules.keep, &rules.pick_tags, &rules.header, &rules.footer] {
+            validate_selectors(selectors)?;
+        }
+        Ok(rules)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "<RemovalRules remove={:?} keep={:?} consent_banners={}>",
+            self.remove, self.keep, self.consent_banners
+        )
+    }
+}
+
+impl RemovalRules {
+    fn selectors(&self, remove_header: bool, remove_footer: bool) -> Vec<&str> {
+        let mut selectors: Vec<&str> = REMOVE_TAGS.to_vec();
+        if self.consent_banners {
+            selectors.extend(consent_containers().map(|selector| -> &str { selector }));
+            selectors.extend(CONSENT_BANNER_TAGS);
+            selectors.extend(CONSENT_CONTAINER_TAGS);
+        }
+        selectors.extend(self.remove.iter().map(|s| s.as_str()));
+        if remove_header {
+            selectors.extend(self.header.iter().map(|s| s.as_str()));
+        }
+        if remove_footer {
+            selectors.extend(self.footer.iter().map(|s| s.as_str()));
+        }
+        selectors
+    }
+
+    pub fn apply(&self, document: &NodeRef, remove_header: bool, remove_footer: bool) {
+        let kept: Vec<NodeRef> = self
+            .keep
+            .iter()
+            .filter_map(|selector| document.select(selector).ok())
+            .flat_map(|nodes| nodes.map(|node| node.as_node().clone()))
+            .collect();
+        for selector in self.selectors(remove_header, remove_footer) {
+            let Ok(nodes) = document.select(selector) else {
+                continue;
+            };
+            for node in nodes.collect::<Vec<_>>() {
+                let node = node.as_node();
+                let holds_kept = kept
+                    .iter()
+                    .any(|kept| kept.inclusive_ancestors().any(|ancestor| ancestor == *node));
+                if !holds_kept {
+                    node.detach();
+                }
+            }
+        }
+    }
+}
+
+/// A rules object, or the name of one given to `register_removal_rules`.
+#[derive(FromPyObject)]
+pub enum RulesArg {
+    Rules(RemovalRules),
+    Name(String),
+}
+
+pub fn resolve_rules(rules: Option<RulesArg>) -> PyResult<RemovalRules> {
+    match rules {
+        None => Ok(RemovalRules::default()),
+        Some(RulesArg::Rules(rules)) => Ok(rules),
+        Some(RulesArg::Name(name)) => RULE_SETS
+            .read()
+            .unwrap()
+            .get(&name)
+            .cloned()
+            .ok_or_else(|| PyKeyError::new_err(format!("no removal rules named {name}"))),
+    }
+}
+
+pub fn register_rules(name: String, rules: RemovalRules) {
+    RULE_SETS.write().unwrap().insert(name, rules);
+}
+
+pub fn unregister_rules(name: &str) -> bool {
+    RULE_SETS.write().unwrap().remove(name).is_some()
+}
+
+pub fn rule_names() -> Vec<String> {
+    let mut names: Vec<String> = RULE_SETS.read().unwrap().keys().cloned().collect();
+    names.sort();
+    names
+}
@@ -0,0 +1,194 @@
+use pyo3::prelude::*;
+use url::Url;
+
+#[pyclass(get_all, module = "html_parsing_tools")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SocialProfile {
+    /// "linkedin", "facebook", "x", "instagram", "youtube", "github" or "tiktok"
+    pub platform: String,
+    /// canonical profile URL, e.g. https://www.linkedin.com/company/acme
+    pub url: String,
+    /// the account name or id from the URL
+    pub handle: String,
+    /// "json_ld" for `sameAs`, "link" for links in the page
+    pub source: String,
+}
+
+// First path segments that are features of the site rather than accounts
+const FACEBOOK_RESERVED: [&str; 14] = [
+    "sharer", "sharer.php", "share", "share.php", "dialog", "plugins", "tr", "login", "hashtag",
+    "events", "groups", "watch", "help", "policies",
+];
+const X_RESERVED: [&str; 12] = [
+    "intent", "share", "home", "hashtag", "search", "i", "login", "signup", "explore", "messages",
+    "settings", "privacy",
+];
+const INSTAGRAM_RESERVED: [&str; 8] =
+    ["p", "reel", "reels", "explore", "accounts", "stories", "tv", "direct"];
+const GITHUB_RESERVED: [&str; 14] = [
+    "features", "login", "join", "sponsors", "about", "marketplace", "topics", "pricing",
+    "settings", "explore", "collections", "trending", "search", "apps",
+];
+
+// Tabs of a profile that are still the profile, as opposed to a post or video on it
+const FACEBOOK_TABS: [&str; 8] =
+    ["about", "photos", "videos", "reviews", "community", "services", "shop", "menu"];
+const X_TABS: [&str; 5] = ["with_replies", "media", "likes", "followers", "following"];
+const INSTAGRAM_TABS: [&str; 3] = ["reels", "tagged", "guides"];
+
+// Nothing after the handle, or one of the profile's own tabs
+fn is_profile_path(rest: &[&str], tabs: &[&str]) -> bool {
+    rest.first().is_none_or(|tab| tabs.contains(tab))
+}
+
+fn segments(url: &Url) -> Vec<&str> {
+    url.path_segments()
+        .map(|segments| segments.filter(|s| !s.is_empty()).collect())
+        .unwrap_or_default()
+}
+
+// A handle made of the characters the platforms allow
+fn is_handle(handle: &str) -> bool {
+    !handle.is_empty()
+        && handle
+            .chars()
+            .all(|c| c.is_alphanumeric() || ['.', '_', '-'].contains(&c))
+}
+
+// `(platform, canonical url, handle)` for a profile URL; None for posts, share dialogs
+// and anything else that isn't an account
+pub fn social_profile(href: &str) -> Option<(&'static str, String, String)> {
+    let href = href.trim();
+    let href = match href.strip_prefix("//") {
+        Some(rest) => format!("https://{rest}"),
+        None => href.to_string(),
+    };
+    let url = Url::parse(&href).ok()?;
+    if !["http", "https"].contains(&url.scheme()) {
+        return None;
+    }
+    let host = url.host_str()?.to_lowercase();
+    // country subdomains like dk.linkedin.com serve the same profiles
+    let host = match host.rsplit_once(".linkedin.") {
+        Some((_, "com")) => "linkedin.com",
+        _ => host
+            .trim_start_matches("www.")
+            .trim_start_matches("m.")
+            .trim_start_matches("mobile."),
+    };
+    let segments = segments(&url);
+    let first = *segments.first()?;
+
+    match host {
+        "linkedin.com" => {
+            let kind = ["company", "in", "school", "showcase"].into_iter().find(|k| *k == first)?;
+            let handle = segments.get(1).filter(|h| is_handle(h))?.to_lowercase();
+            Some(("linkedin", format!("https://www.linkedin.com/{kind}/{handle}"), handle))
+        }
+        "facebook.com" | "fb.com" | "business.facebook.com" => {
+            if first == "profile.php" {
+                let (_, id) = url.query_pairs().find(|(key, _)| key == "id")?;
+                let url = format!("https://www.facebook.com/profile.php?id={id}");
+                return Some(("facebook", url, id.to_string()));
+            }
+            // the id tells apart pages that share a name, "/pages/Acme/123456"
+            if let ("pages" | "people", Some(name), Some(id)) =
+                (first, segments.get(1), segments.get(2))
+            {
+                if is_handle(name) && id.chars().all(|c| c.is_ascii_digit()) {
+                    let name = name.to_lowercase();
+                    let url = format!("https://www.facebook.com/{first}/{name}/{id}");
+                    return Some(("facebook", url, id.to_string()));
+                }
+            }
+            // "/pages/Acme" and "/pg/acme/about"
+            let (handle, rest) = match first {
+                "pages" | "pg" | "people" => (*segments.get(1)?, &segments[2..]),
+                _ => (first, &segments[1..]),
+            };
+            if FACEBOOK_RESERVED.contains(&handle)
+                || !is_handle(handle)
+                || !is_profile_path(rest, &FACEBOOK_TABS)
+            {
+                return None;
+            }
+            let handle = handle.to_lowercase();
+            Some(("facebook", format!("https://www.facebook.com/{handle}"), handle))
+        }
+        "twitter.com" | "x.com" => {
+            let handle = first.trim_start_matches('@');
+            if X_RESERVED.contains(&handle)
+                || !is_handle(handle)
+                || !is_profile_path(&segments[1..], &X_TABS)
+            {
+                return None;
+            }
+            let handle = handle.to_lowercase();
+            Some(("x", format!("https://x.com/{handle}"), handle))
+        }
+        "instagram.com" => {
+            if INSTAGRAM_RESERVED.contains(&first)
+                || !is_handle(first)
+                || !is_profile_path(&segments[1..], &INSTAGRAM_TABS)
+            {
+                return None;
+            }
+            let handle = first.to_lowercase();
+            Some(("instagram", format!("https://www.instagram.com/{handle}"), handle))
+        }
+        "youtube.com" => {
+            let (path, handle) = match first {
+                "channel" | "c" | "user" => {
+                    let name = segments.get(1).filter(|h| is_handle(h))?;
+                    (format!("{first}/{name}"), name.to_string())
+                }
+                _ if first.starts_with('@') && is_handle(&first[1..]) => {
+                    (first.to_string(), first.to_string())
+                }
+                _ => return None,
+            };
+            Some(("youtube", format!("https://www.youtube.com/{path}"), handle))
+        }
+        "github.com" => {
+            let handle = match first {
+                "orgs" => segments.get(1)?,
+                _ => first,
+            };
+            if GITHUB_RESERVED.contains(&handle) || !is_handle(handle) {
+                return None;
+            }
+            let handle = handle.to_lowercase();
+            Some(("github", format!("https://github.com/{handle}"), handle))
+        }
+        "tiktok.com" => {
+            let handle = first.strip_prefix('@').filter(|h| is_handle(h))?.to_lowercase();
+            if !is_profile_path(&segments[1..], &[]) {
+                return None;
+            }
+            Some(("tiktok", format!("https://www.tiktok.com/@{handle}"), handle))
+        }
+        _ => None,
+    }
+}
+
+// Profiles in the order given, each canonical URL once
+pub fn find_social_profiles<'a>(
+    urls: impl IntoIterator<Item = (&'a str, &'static str)>,
+) -> Vec<SocialProfile> {
+    let mut result: Vec<SocialProfile> = vec![];
+    for (href, source) in urls {
+        let Some((platform, url, handle)) = social_profile(href) else {
+            continue;
+        };
+        if result.iter().any(|profile| profile.url == url) {
+            continue;
+        }
+        result.push(SocialProfile {
+            platform: platform.to_string(),
+            url,
+            handle,
+            source: source.to_string(),
+        });
+    }
+    result
+}
@@ -0,0 +1,233 @@
+use crate::content::dom_path;
+use kuchiki::iter::NodeIterator;
+use kuchiki::NodeRef;
+use pyo3::prelude::*;
+
+// Spans past this are typos ("colspan=1000") rather than real layouts
+const MAX_SPAN: usize = 100;
+// Average characters per cell above which a table holds page sections, not values
+const MAX_DATA_CELL_LENGTH: usize = 200;
+
+#[pyclass(get_all, module = "html_parsing_tools")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Table {
+    pub caption: Option<String>,
+    /// one cell per column; header rows spanning several lines are joined with " / "
+    pub headers: Vec<String>,
+    /// every row padded to the same width, with colspan and rowspan cells repeated
+    pub rows: Vec<Vec<String>>,
+    /// true when the table only positions the page and isn't a data table
+    pub is_layout: bool,
+    /// index in the result of the table this one is nested in
+    pub parent: Option<usize>,
+    /// CSS selector of the table element
+    pub path: String,
+}
+
+fn tag_name(node: &NodeRef) -> Option<String> {
+    node.as_element().map(|element| element.name.local.to_string())
+}
+
+fn attribute(node: &NodeRef, name: &str) -> Option<String> {
+    node.as_element()?.attributes.borrow().get(name).map(|value| value.to_string())
+}
+
+fn span(node: &NodeRef, name: &str) -> usize {
+    attribute(node, name)
+        .and_then(|value| value.trim().parse::<usize>().ok())
+        .unwrap_or(1)
+        .min(MAX_SPAN)
+}
+
+// Text of a cell without the text of tables nested inside it
+fn cell_text(node: &NodeRef) -> String {
+    let mut text = String::new();
+    for child in node.children() {
+        if let Some(value) = child.as_text() {
+            text.push_str(&value.borrow());
+            continue;
+        }
+        match tag_name(&child).as_deref() {
+            Some("table" | "script" | "style") => {}
+            Some("br" | "p" | "div" | "li") => {
+                text.push(' ');
+                text.push_str(&cell_text(&child));
+                text.push(' ');
+            }
+            _ => text.push_str(&cell_text(&child)),
+        }
+    }
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+// The rows of this table, not of tables nested in its cells
+fn table_rows(table: &NodeRef) -> Vec<(NodeRef, bool)> {
+    let mut rows: Vec<(NodeRef, bool)> = vec![];
+    for child in table.children() {
+        match tag_name(&child).as_deref() {
+            Some("tr") => rows.push((child, false)),
+            Some(section @ ("thead" | "tbody" | "tfoot")) => {
+                let in_head = section == "thead";
+                for row in child.children() {
+                    if tag_name(&row).as_deref() == Some("tr") {
+                        rows.push((row, in_head));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    rows
+}
+
+struct Cell {
+    text: String,
+    header: bool,
+}
+
+// The HTML table model: each cell goes in the first free slot of its row and fills
+// colspan × rowspan slots
+fn table_grid(rows: &[(NodeRef, bool)]) -> Vec<Vec<Option<Cell>>> {
+    let mut grid: Vec<Vec<Option<Cell>>> = (0..rows.len()).map(|_| vec![]).collect();
+    for (row_ix, (row, _)) in rows.iter().enumerate() {
+        let mut column = 0;
+        for cell in row.children() {
+            let header = match tag_name(&cell).as_deref() {
+                Some("th") => true,
+                Some("td") => false,
+                _ => continue,
+            };
+            while grid[row_ix].get(column).is_some_and(|slot| slot.is_some()) {
+                column += 1;
+            }
+            let text = cell_text(&cell);
+            let colspan = span(&cell, "colspan").max(1);
+            // rowspan="0" runs to the end of the table
+            let rowspan = match span(&cell, "rowspan") {
+                0 => rows.len() - row_ix,
+                rowspan => rowspan.min(rows.len() - row_ix),
+            };
+            for grid_row in grid.iter_mut().skip(row_ix).take(rowspan) {
+                if grid_row.len() < column + colspan {
+                    grid_row.resize_with(column + colspan, || None);
+                }
+                for slot in grid_row.iter_mut().skip(column).take(colspan) {
+                    *slot = Some(Cell {
+                        text: text.clone(),
+                        header,
+                    });
+                }
+            }
+            column += colspan;
+        }
+    }
+    grid
+}
+
+fn is_layout_table(
+    table: &NodeRef,
+    rows: &[Vec<String>],
+    has_headers: bool,
+    has_caption: bool,
+) -> bool {
+    let role = attribute(table, "role").unwrap_or_default();
+    if role == "presentation" || role == "none" {
+        return true;
+    }
+    let columns = rows.first().map_or(0, |row| row.len());
+    if rows.len() < 2 || columns < 2 {
+        return true;
+    }
+    // only this table's own markup; a nested table's caption says nothing about this one
+    let has_data_markup = has_headers
+        || has_caption
+        || table.children().any(|child| tag_name(&child).as_deref() == Some("colgroup"))
+        || attribute(table, "summary").is_some();
+    if has_data_markup {
+        return false;
+    }
+    let has_nested_table = table.descendants().elements().any(|e| &*e.name.local == "table");
+    let has_block_content = table
+        .descendants()
+        .elements()
+        .any(|e| ["div", "form", "section", "article", "nav"].contains(&&*e.name.local));
+    let cells: Vec<&String> = rows.iter().flatten().collect();
+    let average_length =
+        cells.iter().map(|cell| cell.chars().count()).sum::<usize>() / cells.len().max(1);
+    has_nested_table || has_block_content || average_length > MAX_DATA_CELL_LENGTH
+}
+
+fn extract_table(table: &NodeRef, parent: Option<usize>) -> Table {
+    let row_nodes = table_rows(table);
+    let grid = table_grid(&row_nodes);
+    let width = grid.iter().map(|row| row.len()).max().unwrap_or(0);
+
+    // header rows are the thead rows, or the leading rows made only of th cells
+    let header_count = row_nodes
+        .iter()
+        .zip(&grid)
+        .take_while(|((_, in_head), row)| {
+            let all_th = row.iter().all(|slot| slot.as_ref().is_some_and(|cell| cell.header));
+            *in_head || (!row.is_empty() && all_th)
+        })
+        .count();
+    let text_row = |row: &Vec<Option<Cell>>| -> Vec<String> {
+        (0..width)
+            .map(|ix| row.get(ix).and_then(|slot| slot.as_ref()))
+            .map(|slot| slot.map_or(String::new(), |cell| cell.text.clone()))
+            .collect()
+    };
+    let header_rows: Vec<Vec<String>> = grid[..header_count].iter().map(text_row).collect();
+    let header_width = if header_count > 0 { width } else { 0 };
+    let headers: Vec<String> = (0..header_width)
+        .map(|ix| {
+            let mut parts: Vec<&str> = vec![];
+            for row in &header_rows {
+                let text = row[ix].as_str();
+                if !text.is_empty() && parts.last() != Some(&text) {
+                    parts.push(text);
+                }
+            }
+            parts.join(" / ")
+        })
+        .collect();
+    let rows: Vec<Vec<String>> = grid[header_count..]
+        .iter()
+        .map(text_row)
+        .filter(|row| row.iter().any(|cell| !cell.is_empty()))
+        .collect();
+
+    let caption = table
+        .children()
+        .find(|child| tag_name(child).as_deref() == Some("caption"))
+        .map(|caption| cell_text(&caption))
+        .filter(|caption| !caption.is_empty());
+    let all_rows: Vec<Vec<String>> = header_rows.iter().chain(&rows).cloned().collect();
+    let is_layout = is_layout_table(table, &all_rows, header_count > 0, caption.is_some());
+    Table {
+        caption,
+        is_layout,
+        headers,
+        rows,
+        parent,
+        path: dom_path(table),
+    }
+}
+
+// Tables in document order, outer tables before the ones nested in them
+pub fn get_table_list(document: &NodeRef) -> Vec<Table> {
+    let tables: Vec<NodeRef> = document
+        .select("table")
+        .unwrap()
+        .map(|table| table.as_node().clone())
+        .collect();
+    let mut result: Vec<Table> = vec![];
+    for (ix, table) in tables.iter().enumerate() {
+        let parent = table
+            .ancestors()
+            .find(|ancestor| tag_name(ancestor).as_deref() == Some("table"))
+            .and_then(|ancestor| tables[..ix].iter().position(|outer| *outer == ancestor));
+        result.push(extract_table(table, parent));
+    }
+    result
+}
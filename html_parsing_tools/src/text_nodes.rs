@@ -238,14 +238,14 @@ fn join_sentences(sentences: &[&str]) -> String {
 fn regroup_node(node: &[String]) -> Vec<String> {
     let length = node.len();
     if length == 1 {
-        return split_sentence(node.get(0).unwrap(), &SENTENCE_SPLIT_RE, None);
+        return split_sentence(node.first().unwrap(), &SENTENCE_SPLIT_RE, None);
     }
     let mut regrouped: Vec<String> = vec![];
-    let mut group: Vec<&str> = vec![node.get(0).unwrap().as_ref()];
+    let mut group: Vec<&str> = vec![node.first().unwrap().as_ref()];
 
     for ix in 1..length {
         let left = node.get(ix - 1).unwrap().chars().last().unwrap();
-        let right = node.get(ix).unwrap().chars().nth(0).unwrap();
+        let right = node.get(ix).unwrap().chars().next().unwrap();
         let left_category = get_general_category(left);
         let right_category = get_general_category(right);
 
@@ -1,8 +1,10 @@
 use kuchiki::iter::NodeIterator;
-use kuchiki::NodeRef;
+use kuchiki::{Node, NodeRef};
 use lazy_static::lazy_static;
 use regex::Regex;
+use serde_json::{Map, Value};
 use std::collections::{HashMap, HashSet};
+use url::Url;
 
 lazy_static! {
     static ref DESCRIPTION_ATTR_NAMES: HashSet<&'static str> =
@@ -10,21 +12,21 @@ lazy_static! {
 }
 
 pub fn get_lang_internal(document: &NodeRef) -> String {
-    let tag_nodes = document.select("html").unwrap();
-    for tag_node in tag_nodes.collect::<Vec<_>>() {
-        let attributes = tag_node.attributes.borrow();
-        let type_attribute = attributes.get("lang").unwrap_or("");
-        return type_attribute.to_string();
+    match document.select_first("html") {
+        Ok(tag_node) => {
+            let attributes = tag_node.attributes.borrow();
+            attributes.get("lang").unwrap_or("").to_string()
+        }
+        Err(_) => "".to_string(),
     }
-    return "".to_string();
 }
 
 pub fn apply(sentences: Vec<String>, stop_word_regex: &Regex) -> Vec<String> {
     sentences
         .iter()
-        .map(|n| stop_word_regex.replace_all(&n, "").trim().to_string())
+        .map(|n| stop_word_regex.replace_all(n, "").trim().to_string())
         .map(|n| n.split_whitespace().collect::<Vec<&str>>().join(" "))
-        .filter(|n| !n.to_lowercase().contains("cookie") && !n.contains("©") && count_words(n) > 0)
+        .filter(|n| !n.contains("©") && count_words(n) > 0)
         .collect()
 }
 
@@ -38,10 +40,272 @@ pub fn get_json_ld(document: &NodeRef) -> Vec<String> {
             result.push(get_text_string(tag_node.as_node(), " "));
         }
     }
-    return result;
+    result
+}
+
+// <base href> resolved against the page URL, or the page URL itself
+pub fn get_base_url(document: &NodeRef, page_url: Option<&str>) -> Option<Url> {
+    let page_url = page_url.and_then(|url| Url::parse(url).ok());
+    let base_href = document
+        .select_first("base[href]")
+        .ok()
+        .and_then(|base| base.attributes.borrow().get("href").map(|href| href.trim().to_string()));
+    match (page_url, base_href) {
+        (Some(page_url), Some(href)) => page_url.join(&href).ok().or(Some(page_url)),
+        (None, Some(href)) => Url::parse(&href).ok(),
+        (page_url, None) => page_url,
+    }
+}
+
+// Relative URLs are kept as they are when there is nothing to resolve against
+pub fn resolve_url(base_url: Option<&Url>, href: &str) -> String {
+    let href = href.trim();
+    match base_url.and_then(|base_url| base_url.join(href).ok()) {
+        Some(url) => url.to_string(),
+        None => href.to_string(),
+    }
 }
 
-pub fn get_rel_alternate(document: &NodeRef) -> HashMap<String, Vec<String>> {
+fn collapse_whitespace(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+fn push_property(properties: &mut Map<String, Value>, name: &str, value: Value) {
+    match properties.entry(name).or_insert_with(|| Value::Array(vec![])) {
+        Value::Array(values) => values.push(value),
+        _ => unreachable!(),
+    }
+}
+
+fn item_value(types: Vec<String>, id: Option<String>, properties: Map<String, Value>) -> Value {
+    let mut item = Map::new();
+    item.insert(
+        "type".to_string(),
+        Value::Array(types.into_iter().map(Value::String).collect()),
+    );
+    if let Some(id) = id {
+        item.insert("id".to_string(), Value::String(id));
+    }
+    item.insert("properties".to_string(), Value::Object(properties));
+    Value::Object(item)
+}
+
+// Items in the WHATWG microdata JSON shape: {"type": [...], "id": ..., "properties": {...}}
+pub fn get_microdata_items(document: &NodeRef, base_url: Option<&Url>) -> Vec<Value> {
+    let mut ids: HashMap<String, NodeRef> = HashMap::new();
+    for element in document.select("[id]").unwrap() {
+        let id = element.attributes.borrow().get("id").unwrap_or("").to_string();
+        ids.entry(id).or_insert_with(|| element.as_node().clone());
+    }
+
+    document
+        .select("[itemscope]")
+        .unwrap()
+        .filter(|element| !element.attributes.borrow().contains("itemprop"))
+        .map(|element| microdata_item(element.as_node(), &ids, base_url, &mut vec![]))
+        .collect()
+}
+
+fn microdata_item(
+    node: &NodeRef,
+    ids: &HashMap<String, NodeRef>,
+    base_url: Option<&Url>,
+    parents: &mut Vec<NodeRef>,
+) -> Value {
+    let element = node.as_element().unwrap();
+    let attributes = element.attributes.borrow();
+    let types: Vec<String> = attributes
+        .get("itemtype")
+        .unwrap_or("")
+        .split_whitespace()
+        .map(|t| t.to_string())
+        .collect();
+    let id = attributes.get("itemid").map(|id| resolve_url(base_url, id));
+
+    // children first, then whatever itemref points at; pending is popped from the end
+    let mut pending: Vec<NodeRef> = vec![];
+    for id in attributes.get("itemref").unwrap_or("").split_whitespace().rev() {
+        if let Some(referenced) = ids.get(id) {
+            pending.push(referenced.clone());
+        }
+    }
+    pending.extend(node.children().rev());
+    drop(attributes);
+
+    parents.push(node.clone());
+    let mut properties = Map::new();
+    // itemref can point into the item's own subtree, or twice at the same element
+    let mut visited: HashSet<*const Node> = HashSet::new();
+    while let Some(current) = pending.pop() {
+        let Some(current_element) = current.as_element() else {
+            continue;
+        };
+        if !visited.insert(&*current as *const Node) {
+            continue;
+        }
+
+        let attributes = current_element.attributes.borrow();
+        let is_scope = attributes.contains("itemscope");
+        if let Some(names) = attributes.get("itemprop") {
+            let names: Vec<String> = names.split_whitespace().map(|n| n.to_string()).collect();
+            let value = if is_scope {
+                // an itemref loop back to an enclosing item
+                if parents.contains(&current) {
+                    continue;
+                }
+                drop(attributes);
+                microdata_item(&current, ids, base_url, parents)
+            } else {
+                let value = microdata_property_value(&current, &attributes, base_url);
+                drop(attributes);
+                value
+            };
+            for name in names {
+                push_property(&mut properties, &name, value.clone());
+            }
+        } else {
+            drop(attributes);
+        }
+
+        if !is_scope {
+            pending.extend(current.children().rev());
+        }
+    }
+    parents.pop();
+
+    item_value(types, id, properties)
+}
+
+fn microdata_property_value(
+    node: &NodeRef,
+    attributes: &kuchiki::Attributes,
+    base_url: Option<&Url>,
+) -> Value {
+    let tag = node.as_element().unwrap().name.local.to_string();
+    let url = |attribute: &str| resolve_url(base_url, attributes.get(attribute).unwrap_or(""));
+    let value = match tag.as_str() {
+        "meta" => attributes.get("content").unwrap_or("").to_string(),
+        "audio" | "embed" | "iframe" | "img" | "source" | "track" | "video" => url("src"),
+        "a" | "area" | "link" => url("href"),
+        "object" => url("data"),
+        "data" | "meter" => attributes.get("value").unwrap_or("").to_string(),
+        "time" => match attributes.get("datetime") {
+            Some(datetime) => datetime.to_string(),
+            None => collapse_whitespace(&node.text_contents()),
+        },
+        _ => collapse_whitespace(&node.text_contents()),
+    };
+    Value::String(value)
+}
+
+// RDFa Lite (vocab, typeof, property, resource, prefix) in the same shape as microdata
+pub fn get_rdfa_items(document: &NodeRef, base_url: Option<&Url>) -> Vec<Value> {
+    let prefixes: HashMap<String, String> = HashMap::from([
+        ("schema".to_string(), "http://schema.org/".to_string()),
+        ("og".to_string(), "http://ogp.me/ns#".to_string()),
+        ("dc".to_string(), "http://purl.org/dc/terms/".to_string()),
+        ("foaf".to_string(), "http://xmlns.com/foaf/0.1/".to_string()),
+    ]);
+    let mut items: Vec<Value> = vec![];
+    rdfa_walk(document, "", &prefixes, None, &mut items, base_url);
+    items
+}
+
+fn rdfa_walk(
+    node: &NodeRef,
+    vocab: &str,
+    prefixes: &HashMap<String, String>,
+    mut properties: Option<&mut Map<String, Value>>,
+    items: &mut Vec<Value>,
+    base_url: Option<&Url>,
+) {
+    for child in node.children() {
+        let Some(element) = child.as_element() else {
+            continue;
+        };
+        let attributes = element.attributes.borrow();
+        let vocab = attributes.get("vocab").unwrap_or(vocab).to_string();
+        let declared_prefixes;
+        let prefixes = match attributes.get("prefix") {
+            Some(declared) => {
+                let mut declared_words = declared.split_whitespace();
+                let mut extended = prefixes.clone();
+                while let (Some(name), Some(iri)) = (declared_words.next(), declared_words.next()) {
+                    extended.insert(name.trim_end_matches(':').to_string(), iri.to_string());
+                }
+                declared_prefixes = extended;
+                &declared_prefixes
+            }
+            None => prefixes,
+        };
+        let names: Vec<String> = attributes
+            .get("property")
+            .unwrap_or("")
+            .split_whitespace()
+            .map(|n| rdfa_term(n, &vocab, prefixes))
+            .collect();
+        let types: Option<Vec<String>> = attributes.get("typeof").map(|types| {
+            types.split_whitespace().map(|t| rdfa_term(t, &vocab, prefixes)).collect()
+        });
+        let resource = ["resource", "about"]
+            .iter()
+            .find_map(|attribute| attributes.get(*attribute))
+            .map(|resource| resolve_url(base_url, resource));
+
+        if let Some(types) = types {
+            let value = {
+                let mut item_properties = Map::new();
+                drop(attributes);
+                let item_properties_ref = Some(&mut item_properties);
+                rdfa_walk(&child, &vocab, prefixes, item_properties_ref, items, base_url);
+                item_value(types, resource, item_properties)
+            };
+            match (properties.as_deref_mut(), names.is_empty()) {
+                (Some(properties), false) => {
+                    for name in &names {
+                        push_property(properties, name, value.clone());
+                    }
+                }
+                _ => items.push(value),
+            }
+            continue;
+        }
+
+        if let (Some(properties), false) = (properties.as_deref_mut(), names.is_empty()) {
+            let value = match attributes.get("content") {
+                Some(content) => content.to_string(),
+                None => match resource.or_else(|| {
+                    ["href", "src"]
+                        .iter()
+                        .find_map(|attribute| attributes.get(*attribute))
+                        .map(|href| resolve_url(base_url, href))
+                }) {
+                    Some(url) => url,
+                    None => collapse_whitespace(&child.text_contents()),
+                },
+            };
+            for name in &names {
+                push_property(properties, name, Value::String(value.clone()));
+            }
+        }
+        drop(attributes);
+        rdfa_walk(&child, &vocab, prefixes, properties.as_deref_mut(), items, base_url);
+    }
+}
+
+// "LocalBusiness" with vocab "http://schema.org/" gives "http://schema.org/LocalBusiness",
+// for typeof and property alike
+fn rdfa_term(term: &str, vocab: &str, prefixes: &HashMap<String, String>) -> String {
+    if let Some((prefix, name)) = term.split_once(':') {
+        if let Some(iri) = prefixes.get(prefix) {
+            return format!("{iri}{name}");
+        }
+        return term.to_string();
+    }
+    format!("{vocab}{term}")
+}
+
+pub fn get_rel_alternate(document: &NodeRef, base_url: Option<&Url>) -> HashMap<String, Vec<String>> {
     let mut result: HashMap<String, Vec<String>> = HashMap::new();
     let tag_nodes = document.select("link").unwrap();
     for tag_node in tag_nodes.collect::<Vec<_>>() {
@@ -52,20 +316,40 @@ pub fn get_rel_alternate(document: &NodeRef) -> HashMap<String, Vec<String>> {
             if hreflang.is_empty() {
                 continue;
             }
-            if result.contains_key(hreflang.as_str()) {
-                let mut new_data = result.get(hreflang.as_str()).unwrap().clone();
-                let new_array = vec![attributes.get("href").unwrap_or("").to_string()];
-                new_data.extend(new_array);
-                result.insert(hreflang, new_data.to_owned());
-            } else {
-                result.insert(
-                    hreflang,
-                    vec![attributes.get("href").unwrap_or("").to_string()],
-                );
+            let href = match attributes.get("href") {
+                Some(href) => resolve_url(base_url, href),
+                None => "".to_string(),
+            };
+            result.entry(hreflang).or_default().push(href);
+        }
+    }
+    result
+}
+
+// `resolved` is `href` after resolve_url; unresolved relative links count as internal
+pub fn classify_link(href: &str, resolved: &str, page_host: Option<&str>) -> &'static str {
+    let href = href.trim();
+    if href.starts_with('#') {
+        return "anchor";
+    }
+    let Ok(url) = Url::parse(resolved) else {
+        return "internal";
+    };
+    match url.scheme() {
+        "javascript" => "javascript",
+        "mailto" => "mailto",
+        "tel" | "callto" => "tel",
+        "http" | "https" => {
+            let without_www = |host: &str| host.trim_start_matches("www.").to_lowercase();
+            match (url.host_str(), page_host) {
+                (Some(host), Some(page_host)) if without_www(host) == without_www(page_host) => {
+                    "internal"
+                }
+                _ => "external",
             }
         }
+        _ => "external",
     }
-    return result;
 }
 
 pub fn get_descriptions(document: &NodeRef) -> Vec<String> {
@@ -92,6 +376,34 @@ pub fn get_descriptions(document: &NodeRef) -> Vec<String> {
     output
 }
 
+// (lowercased property or name, content) for every non-empty meta tag, in document order;
+// http-equiv keys come back as "http-equiv:<value>"
+pub fn get_meta_values(document: &NodeRef) -> Vec<(String, String)> {
+    let mut result: Vec<(String, String)> = vec![];
+    for tag_node in document.select("meta").unwrap() {
+        let attributes = tag_node.attributes.borrow();
+        let content = attributes.get("content").unwrap_or("").trim();
+        if content.is_empty() {
+            continue;
+        }
+        let key = match (
+            attributes.get("http-equiv"),
+            attributes.get("property"),
+            attributes.get("name"),
+        ) {
+            (Some(http_equiv), _, _) => format!("http-equiv:{}", http_equiv.trim()),
+            (None, Some(property), _) if !property.trim().is_empty() => property.to_string(),
+            (None, _, Some(name)) => name.to_string(),
+            _ => continue,
+        };
+        let key = key.trim().to_lowercase();
+        if !key.is_empty() {
+            result.push((key, content.to_string()));
+        }
+    }
+    result
+}
+
 pub fn get_keywords(document: &NodeRef) -> Option<String> {
     let tag_nodes = document.select("meta").unwrap();
     for tag_node in tag_nodes.collect::<Vec<_>>() {
@@ -104,7 +416,7 @@ pub fn get_keywords(document: &NodeRef) -> Option<String> {
             }
         }
     }
-    return None;
+    None
 }
 
 pub fn get_text_and_remove(document: &NodeRef, tag: &str) -> Vec<String> {
@@ -120,7 +432,7 @@ pub fn get_text_and_remove(document: &NodeRef, tag: &str) -> Vec<String> {
         let as_node = tag_node.as_node();
         as_node.detach();
     }
-    return result;
+    result
 }
 
 pub fn get_text_nodes(node: &NodeRef) -> Vec<String> {
@@ -169,7 +481,7 @@ fn trim_punctuation(n: &str) -> String {
         chars.next_back();
         return chars.as_str().to_string();
     }
-    return n.to_string();
+    n.to_string()
 }
 
 pub fn count_words(s: &str) -> usize {
@@ -0,0 +1,135 @@
+// Dependency resolution (implies) and introspection of the implies graph
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// One `implies` edge: the implied technology and the `\;confidence:N` it was
+/// declared with (100 when absent).
+pub struct Implied {
+    pub name: String,
+    pub confidence: u8,
+}
+
+/// Split an `implies` entry like `PHP\;confidence:50` into name and confidence.
+/// Other `\;` tags (e.g. `version:`) are ignored.
+pub fn parse_implies(s: &str) -> (&str, u8) {
+    let mut parts = s.split("\\;");
+    let name = parts.next().unwrap_or(s).trim();
+    let confidence = parts
+        .filter_map(|tag| tag.strip_prefix("confidence:"))
+        .find_map(|c| c.trim().parse::<u8>().ok())
+        .map_or(100, |c| c.min(100));
+    (name, confidence)
+}
+
+pub struct DependencyResolver {
+    pub implies: HashMap<String, Vec<Implied>>,
+    /// Every technology defined in the database, for dangling-edge checks.
+    pub known: HashSet<String>,
+}
+
+impl DependencyResolver {
+    pub fn resolve(&self, names: &mut HashSet<String>) {
+        let mut queue: Vec<String> = names.iter().cloned().collect();
+        while let Some(name) = queue.pop() {
+            if let Some(implied) = self.implies.get(&name) {
+                for imp in implied {
+                    if names.insert(imp.name.clone()) {
+                        queue.push(imp.name.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    /// `(source, target)` pairs whose target isn't defined in the database.
+    pub fn dangling(&self) -> Vec<(String, String)> {
+        let mut dangling: Vec<(String, String)> = self
+            .implies
+            .iter()
+            .flat_map(|(source, implied)| {
+                implied
+                    .iter()
+                    .filter(|imp| !self.known.contains(&imp.name))
+                    .map(move |imp| (source.clone(), imp.name.clone()))
+            })
+            .collect();
+        dangling.sort();
+        dangling
+    }
+
+    /// Cycles found by a depth-first walk, each rotated to start at its
+    /// smallest name (`["A", "B"]` for `A -> B -> A`). Empty iff the graph is acyclic.
+    pub fn cycles(&self) -> Vec<Vec<String>> {
+        let mut sources: Vec<&String> = self.implies.keys().collect();
+        sources.sort();
+
+        let mut found: HashSet<Vec<String>> = HashSet::new();
+        let mut done: HashSet<&str> = HashSet::new();
+        for source in sources {
+            let mut path: Vec<&str> = Vec::new();
+            self.collect_cycles(source, &mut path, &mut done, &mut found);
+        }
+
+        let mut cycles: Vec<Vec<String>> = found.into_iter().collect();
+        cycles.sort();
+        cycles
+    }
+
+    fn collect_cycles<'a>(
+        &'a self,
+        name: &'a str,
+        path: &mut Vec<&'a str>,
+        done: &mut HashSet<&'a str>,
+        found: &mut HashSet<Vec<String>>,
+    ) {
+        if let Some(start) = path.iter().position(|n| *n == name) {
+            let cycle = &path[start..];
+            let min = (0..cycle.len()).min_by_key(|&i| cycle[i]).unwrap_or(0);
+            let rotated = cycle[min..].iter().chain(&cycle[..min]).map(|n| n.to_string());
+            found.insert(rotated.collect());
+            return;
+        }
+        if done.contains(name) {
+            return;
+        }
+
+        path.push(name);
+        for imp in self.implies.get(name).into_iter().flatten() {
+            self.collect_cycles(&imp.name, path, done, found);
+        }
+        path.pop();
+        done.insert(name);
+    }
+
+    /// Shortest implies path from any of `detected` to `target`, both ends
+    /// included. `None` if `target` isn't reachable.
+    pub fn chain(&self, detected: &[String], target: &str) -> Option<Vec<String>> {
+        let mut parent: HashMap<&str, Option<&str>> = HashMap::new();
+        let mut queue: VecDeque<&str> = VecDeque::new();
+        for name in detected {
+            if parent.insert(name, None).is_none() {
+                queue.push_back(name);
+            }
+        }
+
+        while let Some(name) = queue.pop_front() {
+            if name == target {
+                let mut chain = vec![name.to_string()];
+                let mut current = name;
+                while let Some(Some(prev)) = parent.get(current) {
+                    chain.push(prev.to_string());
+                    current = prev;
+                }
+                chain.reverse();
+                return Some(chain);
+            }
+            for imp in self.implies.get(name).into_iter().flatten() {
+                if !parent.contains_key(imp.name.as_str()) {
+                    parent.insert(&imp.name, Some(name));
+                    queue.push_back(&imp.name);
+                }
+            }
+        }
+        None
+    }
+}
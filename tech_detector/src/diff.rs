@@ -0,0 +1,116 @@
+// Comparing detection results across crawls
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+use pyo3::prelude::*;
+
+/// A technology found in both crawls whose version differs.
+#[pyclass(get_all, module = "tech_detector")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct VersionChange {
+    pub name: String,
+    pub previous: String,
+    pub current: String,
+    /// `True` for a higher version, `False` for a lower one, `None` if the
+    /// versions couldn't be compared.
+    pub upgrade: Option<bool>,
+}
+
+#[pyclass(get_all, module = "tech_detector")]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DetectionDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub version_changed: Vec<VersionChange>,
+    /// Only implied (never detected directly) and newly present
+    pub implied_added: Vec<String>,
+    /// Only implied (never detected directly) and no longer present
+    pub implied_removed: Vec<String>,
+}
+
+/// Compare two crawls of the same site.
+///
+/// `previous` and `current` map directly detected technologies to their version
+/// (`None` when unknown). `*_implied` are the names dependency resolution added.
+/// A technology present in both crawls is never added or removed, even if it
+/// moved between direct and implied; version changes are only reported when
+/// both versions are known.
+#[pyfunction]
+#[pyo3(signature = (
+    previous, current, previous_implied = Vec::new(), current_implied = Vec::new()
+))]
+pub fn diff_detections(
+    previous: HashMap<String, Option<String>>,
+    current: HashMap<String, Option<String>>,
+    previous_implied: Vec<String>,
+    current_implied: Vec<String>,
+) -> DetectionDiff {
+    let prev_all: HashSet<&str> = previous
+        .keys()
+        .chain(&previous_implied)
+        .map(String::as_str)
+        .collect();
+    let curr_all: HashSet<&str> = current
+        .keys()
+        .chain(&current_implied)
+        .map(String::as_str)
+        .collect();
+
+    let mut diff = DetectionDiff::default();
+
+    for &name in curr_all.difference(&prev_all) {
+        if current.contains_key(name) {
+            diff.added.push(name.to_string());
+        } else {
+            diff.implied_added.push(name.to_string());
+        }
+    }
+    for &name in prev_all.difference(&curr_all) {
+        if previous.contains_key(name) {
+            diff.removed.push(name.to_string());
+        } else {
+            diff.implied_removed.push(name.to_string());
+        }
+    }
+
+    for (name, prev_version) in &previous {
+        let (Some(Some(curr)), Some(prev)) = (current.get(name), prev_version) else {
+            continue;
+        };
+        if prev != curr {
+            diff.version_changed.push(VersionChange {
+                name: name.clone(),
+                previous: prev.clone(),
+                current: curr.clone(),
+                upgrade: compare_versions(prev, curr).map(|o| o == Ordering::Less),
+            });
+        }
+    }
+
+    diff.added.sort();
+    diff.removed.sort();
+    diff.implied_added.sort();
+    diff.implied_removed.sort();
+    diff.version_changed.sort_by(|a, b| a.name.cmp(&b.name));
+    diff
+}
+
+/// Compare dotted versions numerically (`2.10` > `2.9`, `3` == `3.0`).
+/// `None` if either has a non-numeric component.
+pub fn compare_versions(a: &str, b: &str) -> Option<Ordering> {
+    let parse = |v: &str| -> Option<Vec<u64>> {
+        v.trim_start_matches(['v', 'V'])
+            .split('.')
+            .map(|p| p.parse::<u64>().ok())
+            .collect()
+    };
+    let (a, b) = (parse(a)?, parse(b)?);
+    for i in 0..a.len().max(b.len()) {
+        let ord = a.get(i).unwrap_or(&0).cmp(b.get(i).unwrap_or(&0));
+        if ord != Ordering::Equal {
+            return Some(ord);
+        }
+    }
+    Some(Ordering::Equal)
+}
@@ -1,187 +1,433 @@
-// DOM selector → regex conversion — mirrors oceanai/utils/technologies.py dom_to_regex()
+// DOM selector → regex conversion. Started as a port of oceanai/utils/technologies.py
+// dom_to_regex(), which only knew a fixed list of tags and attributes; selectors
+// are now parsed generically and anything that can't be translated is reported.
 
-use once_cell::sync::Lazy;
-use regex::Regex;
 use serde_json::Value;
 
-const XPATH_TAGS: &[&str] = &[
-    "a", "amp-img", "body", "button", "clipPath", "div", "embed", "form", "html", "iframe",
-    "img", "input", "link", "meta", "noscript", "object", "param", "script", "style", "section",
-    "source", "title", "track", "video",
-];
+use crate::patterns::{self, TextPattern};
 
-const XPATH_PROPS: &[&str] = &[
-    "action",
-    "class",
-    "data-abuse-proto",
-    "data-animation-type",
-    "data-bg",
-    "data-function",
-    "data-hid",
-    "data-lazy-src",
-    "data-meta",
-    "data-name",
-    "data-quadpay-src",
-    "data-page",
-    "data-src",
-    "data-srcset",
-    "data-requiremodule",
-    "data-testid",
-    "data-video-provider",
-    "href",
-    "id",
-    "imagesrcset",
-    "name",
-    "poster",
-    "scr",
-    "src",
-    "srcset",
-    "style",
-    "title",
-    "type",
-    "value",
-];
+/// A selector (or one of its checks) that produced no pattern, and why.
+pub struct Untranslated {
+    pub selector: String,
+    pub reason: String,
+}
+
+pub struct DomPatterns {
+    pub patterns: Vec<TextPattern>,
+    pub untranslated: Vec<Untranslated>,
+}
 
-const DOM_STOPWORDS: &[&str] = &["browser", "footer", "print-only", ">"];
+// ── Selector parsing ─────────────────────────────────────────────────────────
 
-// Matches: tag[prop*='value'] or tag[prop^='value'] or tag[prop='value']
-static DOM_RE: Lazy<Regex> = Lazy::new(|| {
-    let tags = XPATH_TAGS.join("|");
-    let props = XPATH_PROPS.join("|");
-    Regex::new(&format!(
-        r"({tags})\[({props})[\*\^]?\='?(.*?)'?\]"
-    ))
-    .unwrap()
-});
+/// DOM properties that mirror an HTML attribute, so they can be checked in markup.
+/// Anything else (`_reactRootContainer`, `__vue__`, ...) only exists at runtime.
+const REFLECTED_PROPS: &[(&str, &str)] = &[
+    ("action", "action"),
+    ("alt", "alt"),
+    ("className", "class"),
+    ("content", "content"),
+    ("href", "href"),
+    ("htmlFor", "for"),
+    ("id", "id"),
+    ("lang", "lang"),
+    ("name", "name"),
+    ("rel", "rel"),
+    ("src", "src"),
+    ("title", "title"),
+    ("type", "type"),
+    ("value", "value"),
+];
 
-// Matches: tag[prop]  (presence-only attribute check)
-static DOM2_RE: Lazy<Regex> = Lazy::new(|| {
-    let tags = XPATH_TAGS.join("|");
-    Regex::new(&format!(r"({tags})\[([a-zA-Z\-]+)\]")).unwrap()
-});
+/// Conditions past this many are dropped, since every ordering of attributes
+/// becomes its own alternative. Dropping one only makes the match broader.
+const MAX_CONDITIONS: usize = 3;
 
-fn get_dom_1(tag: &str) -> String {
-    format!("<(?:[^>]*?){tag}")
-}
+// Anything allowed inside a tag between two attributes. It has to start at a
+// boundary so `data-foo` isn't satisfied by `data-foobar`. Attribute conditions
+// stop right after the value without consuming a terminator, so the gap or tag
+// end that follows is also what ends an unquoted value like `id=app>`.
+const ATTR_GAP: &str = r#"(?:[\s"'/=][^>]*?)?"#;
+const TAG_END: &str = r#"(?:[\s"'/=][^>]*)?>"#;
 
-fn get_dom_2(tag: &str, prop: &str) -> String {
-    format!("<(?:[^>]*?){tag}(.*?){prop}")
+enum AttrOp {
+    Exists,
+    Equals,
+    Contains,
+    Prefix,
+    Suffix,
+    Word,
+    DashPrefix,
 }
 
-fn get_dom_3(tag: &str, prop: &str, value: &str) -> String {
-    format!(r#"<{tag}\s+(?:[^>]*?\s+)?{prop}=(["'])(.*?){value}(.*?)"#)
+/// The last compound of a selector: an optional tag plus attribute checks.
+struct Compound {
+    tag: Option<String>,
+    attrs: Vec<(String, AttrOp, String)>,
 }
 
-fn dom_list_to_regex(doms: &[&str]) -> Vec<String> {
-    let mut regexes = Vec::new();
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '_'
+}
 
-    for dom_list in doms {
-        for dom in dom_list.split(", ") {
-            let dom = dom.trim();
+/// Only the element the selector finally targets can be checked in markup,
+/// so ancestors and siblings before a combinator are ignored.
+fn last_compound(selector: &str) -> &str {
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in selector.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            ' ' | '>' | '+' | '~' if depth == 0 => start = i + c.len_utf8(),
+            _ => {}
+        }
+    }
+    &selector[start..]
+}
 
-            // tag[prop=value] matches
-            for cap in DOM_RE.captures_iter(dom) {
-                let prop = cap.get(2).map_or("", |m| m.as_str());
-                let value = cap.get(3).map_or("", |m| m.as_str());
-                if prop == "hidden" || value == "hidden" {
-                    continue;
-                }
-                let tag = cap.get(1).map_or("", |m| m.as_str());
-                regexes.push(get_dom_3(tag, prop, &value.replace('.', "\\.")));
+/// Split a selector list on top-level commas (not inside `[...]` or quotes).
+fn split_selectors(selectors: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    let mut start = 0;
+    for (i, c) in selectors.char_indices() {
+        match (c, quote) {
+            ('"' | '\'', None) => quote = Some(c),
+            (q, Some(open)) if q == open => quote = None,
+            (_, Some(_)) => {}
+            ('[', None) => depth += 1,
+            (']', None) => depth -= 1,
+            (',', None) if depth == 0 => {
+                parts.push(selectors[start..i].trim());
+                start = i + 1;
             }
+            _ => {}
+        }
+    }
+    parts.push(selectors[start..].trim());
+    parts.retain(|p| !p.is_empty());
+    parts
+}
 
-            // tag[prop] presence matches
-            for cap in DOM2_RE.captures_iter(dom) {
-                let tag = cap.get(1).map_or("", |m| m.as_str());
-                let prop = cap.get(2).map_or("", |m| m.as_str());
-                regexes.push(get_dom_2(tag, prop));
-            }
+fn parse_compound(selector: &str) -> Result<Compound, String> {
+    let s = last_compound(selector.trim());
+    if s.is_empty() {
+        return Err("empty selector".to_string());
+    }
+    let mut chars = s.chars().peekable();
 
-            if dom.starts_with('.') {
-                // .className → treat class part as tag name
-                if let Some(class) = dom.splitn(2, '.').nth(1) {
-                    regexes.push(get_dom_1(class));
-                }
-            } else if dom.starts_with("div.") {
-                if let Some(class) = dom.splitn(2, '.').nth(1) {
-                    regexes.push(get_dom_2("div", class));
-                }
-            } else if dom.contains('#') {
-                let parts: Vec<&str> = dom.splitn(2, '#').collect();
-                if parts.len() == 2 {
-                    if parts[0].is_empty() {
-                        regexes.push(get_dom_1(parts[1]));
-                    } else {
-                        regexes.push(get_dom_2(parts[0], &parts[1].replace('\'', "\\'")));
-                    }
+    let tag: String =
+        std::iter::from_fn(|| chars.next_if(|c| is_ident_char(*c) || *c == '*')).collect();
+    let tag = match tag.as_str() {
+        "" | "*" => None,
+        t if t.contains('*') => return Err(format!("invalid tag name `{t}`")),
+        t => Some(t.to_string()),
+    };
+
+    let mut attrs = Vec::new();
+    while let Some(c) = chars.next() {
+        match c {
+            '#' | '.' => {
+                let ident: String =
+                    std::iter::from_fn(|| chars.next_if(|c| is_ident_char(*c))).collect();
+                if ident.is_empty() {
+                    return Err(format!("`{c}` without a name"));
                 }
-            } else if dom.starts_with('[') && dom.ends_with(']') {
-                let inner = &dom[1..dom.len() - 1];
-                if dom.contains('=') {
-                    // normalise ^= and *= to =
-                    let normalised = inner.replace("^=", "=").replace("*=", "=");
-                    let kv: Vec<&str> = normalised.splitn(2, '=').collect();
-                    if kv.len() == 2 {
-                        regexes.push(get_dom_2(kv[0], kv[1]));
-                    }
+                if c == '#' {
+                    attrs.push(("id".to_string(), AttrOp::Equals, ident));
                 } else {
-                    regexes.push(get_dom_1(inner));
+                    attrs.push(("class".to_string(), AttrOp::Word, ident));
+                }
+            }
+            '[' => {
+                let inner: String = std::iter::from_fn(|| chars.next_if(|c| *c != ']')).collect();
+                if chars.next().is_none() {
+                    return Err("unclosed `[`".to_string());
                 }
+                attrs.push(parse_attribute(&inner)?);
             }
+            ':' => {
+                let pseudo: String = std::iter::once(c).chain(chars.by_ref()).collect();
+                return Err(format!("pseudo-class `{pseudo}` can't be checked in markup"));
+            }
+            other => return Err(format!("unexpected `{other}`")),
+        }
+    }
+
+    Ok(Compound { tag, attrs })
+}
+
+fn parse_attribute(inner: &str) -> Result<(String, AttrOp, String), String> {
+    let invalid = || format!("invalid attribute selector `[{inner}]`");
+    let Some(eq) = inner.find('=') else {
+        let name = inner.trim();
+        if name.is_empty() || !name.chars().all(|c| is_ident_char(c) || c == ':') {
+            return Err(invalid());
         }
+        return Ok((name.to_string(), AttrOp::Exists, String::new()));
+    };
+
+    let (op, name_end) = match inner[..eq].chars().last() {
+        Some('*') => (AttrOp::Contains, eq - 1),
+        Some('^') => (AttrOp::Prefix, eq - 1),
+        Some('$') => (AttrOp::Suffix, eq - 1),
+        Some('~') => (AttrOp::Word, eq - 1),
+        Some('|') => (AttrOp::DashPrefix, eq - 1),
+        _ => (AttrOp::Equals, eq),
+    };
+    let name = inner[..name_end].trim();
+    if name.is_empty() || !name.chars().all(|c| is_ident_char(c) || c == ':') {
+        return Err(invalid());
+    }
+
+    let mut value = inner[eq + 1..].trim();
+    // trailing case-sensitivity flag: [type="a" i]
+    if let Some(v) = value.strip_suffix(" i").or_else(|| value.strip_suffix(" s")) {
+        value = v.trim_end();
+    }
+    let value = value.trim_matches(|c| c == '"' || c == '\'');
+    Ok((name.to_string(), op, value.to_string()))
+}
+
+// ── Regex building ───────────────────────────────────────────────────────────
+
+fn attr_condition(name: &str, op: &AttrOp, value: &str) -> String {
+    let name = regex::escape(name);
+    let value = regex::escape(value);
+    let eq = r#"\s*=\s*["']?"#;
+    match op {
+        AttrOp::Exists => format!(r"\s{name}"),
+        AttrOp::Equals => format!(r"\s{name}{eq}{value}"),
+        AttrOp::Contains => format!(r#"\s{name}{eq}[^"'>]*?{value}[^"'>]*"#),
+        AttrOp::Prefix => format!(r#"\s{name}{eq}{value}[^"'>]*"#),
+        AttrOp::Suffix => format!(r#"\s{name}{eq}[^"'>]*?{value}"#),
+        AttrOp::Word => format!(r#"\s{name}{eq}(?:[^"'>]*?\s)?{value}"#),
+        AttrOp::DashPrefix => format!(r#"\s{name}{eq}{value}(?:-[^"'>]*)?"#),
+    }
+}
+
+/// A fingerprint value pattern (from `attributes`, `properties` or `text`) split
+/// into its regex, whether it was anchored at either end, and its version template.
+struct ValuePattern {
+    regex: String,
+    anchored_start: bool,
+    anchored_end: bool,
+    version: Option<String>,
+}
+
+/// `Ok(None)` when the fingerprint itself disables the pattern (`confidence:0`).
+fn value_pattern(raw: &str) -> Result<Option<ValuePattern>, String> {
+    let version = patterns::version_template(raw);
+    let Some(text) = patterns::cut_version(raw) else {
+        return Ok(None);
+    };
+    if patterns::is_unsupported(&text) {
+        return Err(format!("pattern `{text}` uses lookaround or backreferences"));
     }
+    let (text, anchored_start) = match text.strip_prefix('^') {
+        Some(rest) => (rest, true),
+        None => (text.as_str(), false),
+    };
+    let (text, anchored_end) = match text.strip_suffix('$') {
+        Some(rest) if !rest.ends_with('\\') => (rest, true),
+        _ => (text, false),
+    };
+    Ok(Some(ValuePattern {
+        regex: patterns::replace_js_regexp(text),
+        anchored_start,
+        anchored_end,
+        version,
+    }))
+}
 
-    regexes
+/// An attribute whose value must match a fingerprint pattern; an empty pattern
+/// only requires the attribute to be present.
+fn attr_pattern_condition(
+    name: &str,
+    raw: &str,
+) -> Result<Option<(String, Option<String>)>, String> {
+    if raw.is_empty() {
+        return Ok(Some((attr_condition(name, &AttrOp::Exists, ""), None)));
+    }
+    let Some(vp) = value_pattern(raw)? else {
+        return Ok(None);
+    };
+    let name = regex::escape(name);
+    let lead = if vp.anchored_start { "" } else { r#"[^"'>]*?"# };
+    let tail = if vp.anchored_end { "" } else { r#"[^"'>]*"# };
+    let condition = format!(r#"\s{name}\s*=\s*["']?{lead}(?:{}){tail}"#, vp.regex);
+    Ok(Some((condition, vp.version)))
 }
 
-fn dom_dict_to_regex(doms: &serde_json::Map<String, Value>) -> (Vec<String>, Vec<String>) {
-    let mut regexes = Vec::new();
-    let mut doms_list = Vec::new();
+/// Opening-tag regexes for every ordering of `conditions`, plus `tail`.
+fn tag_alternatives(tag: &Option<String>, conditions: &[String], tail: &str) -> Vec<String> {
+    let tag = match tag {
+        Some(t) => regex::escape(t),
+        None => r"[a-zA-Z][\w:-]*".to_string(),
+    };
+    permutations(conditions)
+        .into_iter()
+        .map(|order| {
+            let attrs: String = order.iter().map(|c| format!("{ATTR_GAP}{c}")).collect();
+            format!("<{tag}{attrs}{tail}")
+        })
+        .collect()
+}
 
-    for (keys, item) in doms {
-        if keys.ends_with('*') || DOM_STOPWORDS.iter().any(|sw| keys.contains(sw)) {
-            continue;
+fn permutations(items: &[String]) -> Vec<Vec<String>> {
+    if items.len() <= 1 {
+        return vec![items.to_vec()];
+    }
+    let mut result = Vec::new();
+    for i in 0..items.len() {
+        let mut rest = items.to_vec();
+        let first = rest.remove(i);
+        for mut order in permutations(&rest) {
+            order.insert(0, first.clone());
+            result.push(order);
         }
+    }
+    result
+}
+
+// ── Conversion ───────────────────────────────────────────────────────────────
 
-        for key in keys.split(',') {
-            let key = key.trim();
-
-            if XPATH_TAGS.contains(&key) {
-                if let Value::Object(obj) = item {
-                    if let Some(attrs) = obj.get("attributes").and_then(|a| a.as_object()) {
-                        for (attr_key, attr_val) in attrs {
-                            let attr_val = attr_val.as_str().unwrap_or("").replace('.', "\\.");
-                            regexes.push(get_dom_3(key, attr_key, &attr_val));
-                        }
-                    } else if let Some(text) = obj.get("text").and_then(|t| t.as_str()) {
-                        regexes.push(get_dom_3(key, "text", &text.replace('.', "\\.")));
-                    }
+/// Patterns for one selector and the checks listed under it
+/// (`exists`, `text`, `attributes`, `properties`).
+fn selector_patterns(
+    selector: &str,
+    checks: &serde_json::Map<String, Value>,
+    untranslated: &mut Vec<Untranslated>,
+) -> Vec<TextPattern> {
+    let mut report = |reason: String| {
+        untranslated.push(Untranslated { selector: selector.to_string(), reason });
+    };
+    let compound = match parse_compound(selector) {
+        Ok(compound) => compound,
+        Err(reason) => {
+            report(reason);
+            return vec![];
+        }
+    };
+    let base: Vec<(&str, String)> = compound
+        .attrs
+        .iter()
+        .map(|(name, op, value)| (name.as_str(), attr_condition(name, op, value)))
+        .collect();
+
+    let mut result = Vec::new();
+    // `extra` is a check on one attribute; selector checks on that same
+    // attribute are dropped, as both can't be ordered within a single tag.
+    let mut push = |extra: Option<(&str, String)>, tail: String, version: Option<String>| {
+        let mut conditions: Vec<String> = base
+            .iter()
+            .filter(|(name, _)| {
+                extra.as_ref().is_none_or(|(attr, _)| !attr.eq_ignore_ascii_case(name))
+            })
+            .map(|(_, condition)| condition.clone())
+            .collect();
+        let limit = MAX_CONDITIONS - usize::from(extra.is_some());
+        conditions.truncate(limit);
+        conditions.extend(extra.map(|(_, condition)| condition));
+        let alternatives = tag_alternatives(&compound.tag, &conditions, &tail);
+        result.push(TextPattern { alternatives, version });
+    };
+
+    if checks.contains_key("exists") {
+        push(None, TAG_END.to_string(), None);
+    }
+
+    if let Some(text) = checks.get("text").and_then(Value::as_str) {
+        if text.is_empty() {
+            push(None, TAG_END.to_string(), None);
+        } else {
+            match value_pattern(text) {
+                Ok(Some(vp)) => {
+                    let lead = if vp.anchored_start { r"\s*" } else { "[^<]*?" };
+                    let end = if vp.anchored_end { r"\s*<" } else { "" };
+                    push(None, format!("{TAG_END}{lead}(?:{}){end}", vp.regex), vp.version);
                 }
-            } else {
-                doms_list.push(key.to_string());
+                Ok(None) => {}
+                Err(reason) => report(format!("text: {reason}")),
             }
         }
     }
 
-    (regexes, doms_list)
+    let mut attr_checks: Vec<(&str, &str)> = Vec::new();
+    if let Some(attrs) = checks.get("attributes").and_then(Value::as_object) {
+        for (name, val) in attrs {
+            attr_checks.push((name.as_str(), val.as_str().unwrap_or("")));
+        }
+    }
+    if let Some(props) = checks.get("properties").and_then(Value::as_object) {
+        for (prop, val) in props {
+            match REFLECTED_PROPS.iter().find(|(p, _)| p == prop) {
+                Some((_, attr)) => attr_checks.push((attr, val.as_str().unwrap_or(""))),
+                None => report(format!("property `{prop}` only exists at runtime")),
+            }
+        }
+    }
+    for (name, raw) in attr_checks {
+        match attr_pattern_condition(name, raw) {
+            Ok(Some((condition, version))) => {
+                push(Some((name, condition)), TAG_END.to_string(), version)
+            }
+            Ok(None) => {}
+            Err(reason) => report(format!("attribute `{name}`: {reason}")),
+        }
+    }
+
+    if !["exists", "text", "attributes", "properties"].iter().any(|k| checks.contains_key(*k)) {
+        report("no exists, text, attributes or properties check".to_string());
+    }
+    if compound.tag.is_none() && compound.attrs.is_empty() && !result.is_empty() {
+        // `*` alone would match every element
+        result.clear();
+        report("selector matches any element".to_string());
+    }
+
+    result
 }
 
-/// Convert a `dom` field value (string, list, or dict) into regex strings.
-pub fn dom_to_regex(value: &Value) -> Vec<String> {
+/// Convert a `dom` field value (string, list, or dict) into text patterns.
+/// List entries only require the element to exist.
+pub fn dom_to_regex(value: &Value) -> DomPatterns {
+    let mut patterns = Vec::new();
+    let mut untranslated = Vec::new();
+
+    let exists: serde_json::Map<String, Value> =
+        [("exists".to_string(), Value::String(String::new()))].into_iter().collect();
+    let mut unsupported = |selector: String, reason: &str| {
+        untranslated.push(Untranslated { selector, reason: reason.to_string() });
+    };
+    let mut entries: Vec<(&str, &serde_json::Map<String, Value>)> = Vec::new();
     match value {
-        Value::String(s) => {
-            dom_list_to_regex(&[s.as_str()])
-        }
+        Value::String(s) => entries.push((s.as_str(), &exists)),
         Value::Array(arr) => {
-            let strs: Vec<&str> = arr.iter().filter_map(|v| v.as_str()).collect();
-            dom_list_to_regex(&strs)
+            for item in arr {
+                match item.as_str() {
+                    Some(s) => entries.push((s, &exists)),
+                    None => unsupported(item.to_string(), "list entry is not a selector string"),
+                }
+            }
         }
         Value::Object(obj) => {
-            let (mut regexes, list_items) = dom_dict_to_regex(obj);
-            let strs: Vec<&str> = list_items.iter().map(|s| s.as_str()).collect();
-            regexes.extend(dom_list_to_regex(&strs));
-            regexes
+            for (keys, item) in obj {
+                match item.as_object() {
+                    Some(checks) => entries.push((keys.as_str(), checks)),
+                    None => unsupported(keys.clone(), "checks are not an object"),
+                }
+            }
         }
-        _ => vec![],
+        other => unsupported(other.to_string(), "dom is not a string, list or object"),
     }
+
+    for (selectors, checks) in entries {
+        for selector in split_selectors(selectors) {
+            patterns.extend(selector_patterns(selector, checks, &mut untranslated));
+        }
+    }
+
+    DomPatterns { patterns, untranslated }
 }
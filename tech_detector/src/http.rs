@@ -0,0 +1,132 @@
+// HTTP input model: one exchange per fetched page, one hop per response in the
+// redirect chain
+
+use std::collections::HashMap;
+
+use pyo3::prelude::*;
+
+/// One response in a redirect chain. Headers keep their original order and casing;
+/// matching lowercases names and trims values.
+#[pyclass(get_all, module = "tech_detector")]
+#[derive(Clone)]
+pub struct HttpHop {
+    url: String,
+    status: u16,
+    headers: Vec<(String, String)>,
+}
+
+#[pymethods]
+impl HttpHop {
+    #[new]
+    #[pyo3(signature = (url, status, headers = Vec::new()))]
+    pub fn new(url: String, status: u16, headers: Vec<(String, String)>) -> Self {
+        HttpHop { url, status, headers }
+    }
+
+    /// `(name, value)` of every `Set-Cookie` header on this hop.
+    pub fn cookies(&self) -> Vec<(String, String)> {
+        self.headers
+            .iter()
+            .filter(|(name, _)| name.eq_ignore_ascii_case("set-cookie"))
+            .filter_map(|(_, value)| parse_set_cookie(value))
+            .collect()
+    }
+}
+
+impl HttpHop {
+    /// Lowercased header name → every value sent under it, in order.
+    pub fn header_map(&self) -> HashMap<String, Vec<String>> {
+        let mut map: HashMap<String, Vec<String>> = HashMap::new();
+        for (name, value) in &self.headers {
+            map.entry(name.trim().to_lowercase())
+                .or_default()
+                .push(value.trim().to_string());
+        }
+        map
+    }
+
+    pub fn cookie_map(&self) -> HashMap<String, Vec<String>> {
+        let mut map: HashMap<String, Vec<String>> = HashMap::new();
+        for (name, value) in self.cookies() {
+            map.entry(name.to_lowercase()).or_default().push(value);
+        }
+        map
+    }
+}
+
+/// A page fetch: the URL asked for, where it ended up and every hop in between.
+#[pyclass(get_all, module = "tech_detector")]
+#[derive(Clone)]
+pub struct HttpExchange {
+    request_url: String,
+    final_url: String,
+    hops: Vec<HttpHop>,
+}
+
+#[pymethods]
+impl HttpExchange {
+    /// `final_url` defaults to the URL of the last hop.
+    #[new]
+    #[pyo3(signature = (request_url, hops, final_url = None))]
+    pub fn new(request_url: String, hops: Vec<HttpHop>, final_url: Option<String>) -> Self {
+        let final_url = final_url
+            .or_else(|| hops.last().map(|h| h.url.clone()))
+            .unwrap_or_else(|| request_url.clone());
+        HttpExchange { request_url, final_url, hops }
+    }
+
+    /// Final response status, if there was any response.
+    pub fn status(&self) -> Option<u16> {
+        self.hops.last().map(|h| h.status)
+    }
+
+    /// Cookies set anywhere along the redirect chain, in order.
+    pub fn cookies(&self) -> Vec<(String, String)> {
+        self.hops.iter().flat_map(HttpHop::cookies).collect()
+    }
+}
+
+impl HttpExchange {
+    pub fn hops(&self) -> &[HttpHop] {
+        &self.hops
+    }
+}
+
+/// A technology found in the headers or cookies of one hop.
+#[pyclass(get_all, module = "tech_detector")]
+#[derive(Clone)]
+pub struct HopMatch {
+    pub name: String,
+    /// `headers` or `cookies`
+    pub field: String,
+    /// Index into the exchanges passed in
+    pub exchange: usize,
+    /// Index into that exchange's hops; 0 is the first response
+    pub hop: usize,
+    pub url: String,
+    pub status: u16,
+}
+
+impl HopMatch {
+    pub fn new(name: String, field: &str, exchange: usize, hop_idx: usize, hop: &HttpHop) -> Self {
+        HopMatch {
+            name,
+            field: field.to_string(),
+            exchange,
+            hop: hop_idx,
+            url: hop.url.clone(),
+            status: hop.status,
+        }
+    }
+}
+
+/// `name=value; Path=/; HttpOnly` → `(name, value)`. Quotes around the value are dropped.
+pub fn parse_set_cookie(header: &str) -> Option<(String, String)> {
+    let pair = header.split(';').next()?;
+    let (name, value) = pair.split_once('=')?;
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+    Some((name.to_string(), value.trim().trim_matches('"').to_string()))
+}
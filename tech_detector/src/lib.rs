@@ -1,12 +1,23 @@
+mod deps;
+mod diff;
 mod dom;
+mod http;
+mod lint;
 mod patterns;
+mod report;
 
 use std::collections::{HashMap, HashSet};
 
+use deps::{DependencyResolver, Implied};
+use diff::{DetectionDiff, VersionChange};
+use http::{HopMatch, HttpExchange, HttpHop};
+use lint::Diagnostic;
+use patterns::TextPattern;
 use pyo3::prelude::*;
 use pyo3::types::PyDict;
 use rayon::prelude::*;
-use regex::Regex;
+use regex::{Regex, RegexBuilder, RegexSet};
+use report::{Detection, DetectionReport, PatternStats};
 use regex_automata::{
     hybrid::dfa::{DFA, OverlappingState},
     util::syntax::Config as SyntaxConfig,
@@ -21,8 +32,17 @@ struct TextChunk {
     names: Vec<String>,
 }
 
+/// Patterns carrying a `\;version:` template, re-run with captures once the
+/// DFA has found their technology.
+struct VersionPattern {
+    regexes: Vec<Regex>,
+    template: String,
+    name: String,
+}
+
 struct TextMatcher {
     chunks: Vec<TextChunk>,
+    versioned: Vec<VersionPattern>,
     total_patterns: usize,
 }
 
@@ -76,61 +96,238 @@ impl TextMatcher {
 
         matched.into_iter().collect()
     }
+
+    /// Versions for the already-matched `names`, from the first pattern and
+    /// part that yields a non-empty version.
+    fn match_versions(
+        &self,
+        parts: &[Vec<u8>],
+        names: &HashSet<String>,
+    ) -> HashMap<String, String> {
+        let mut versions: HashMap<String, String> = HashMap::new();
+        for vp in &self.versioned {
+            if versions.contains_key(&vp.name) || !names.contains(&vp.name) {
+                continue;
+            }
+            let found = parts
+                .iter()
+                .filter_map(|part| std::str::from_utf8(part).ok())
+                .flat_map(|text| vp.regexes.iter().filter_map(move |re| re.captures(text)))
+                .find_map(|caps| patterns::expand_version(&vp.template, &caps));
+            if let Some(version) = found {
+                versions.insert(vp.name.clone(), version);
+            }
+        }
+        versions
+    }
 }
 
 // ── Dict matching (headers, cookies, meta) ────────────────────────────────────
 
 struct DictEntry {
     value_regex: Option<Regex>,
+    /// `\;version:` template and the regex whose captures fill it
+    version: Option<(Regex, String)>,
+    name: String,
+}
+
+/// A dict value pattern as read from the JSON, before compiling.
+struct DictPattern {
+    value: Option<String>,
+    /// `(pattern, template)`; kept apart from `value` because a presence-only
+    /// value like `^(.+)$\;version:\1` still carries a version
+    version: Option<(String, String)>,
     name: String,
 }
 
+/// Keys containing regex syntax (e.g. cookie `wordpress_[a-z0-9_]+`) can't be
+/// looked up directly; they're compiled into one indexed `RegexSet` that every
+/// key is matched against in addition to the exact-key lookup.
 struct DictMatcher {
     entries: HashMap<String, Vec<DictEntry>>,
+    key_set: RegexSet,
+    key_entries: Vec<Vec<DictEntry>>,
 }
 
 impl DictMatcher {
-    fn match_dict(&self, data: &HashMap<String, String>) -> Vec<String> {
+    /// `data` maps each key to all of its values (repeated headers, meta tags
+    /// sharing a name); an entry matches if any one value does.
+    fn match_dict(&self, data: &HashMap<String, Vec<String>>) -> Vec<String> {
         let mut names = Vec::new();
-        for (raw_key, value) in data {
-            let key = raw_key.to_lowercase();
-            if let Some(entries) = self.entries.get(&key) {
-                for entry in entries {
-                    let matched = match &entry.value_regex {
-                        None => true,
-                        Some(re) => re.is_match(value),
-                    };
-                    if matched {
-                        names.push(entry.name.clone());
-                    }
-                }
+        for (raw_key, values) in data {
+            for entries in self.key_matches(&raw_key.to_lowercase()) {
+                match_entries(entries, values, &mut names);
             }
         }
         names
     }
+
+    /// Versions for the already-matched `names`, from the first value that
+    /// yields a non-empty version.
+    fn match_versions(
+        &self,
+        data: &HashMap<String, Vec<String>>,
+        names: &HashSet<String>,
+    ) -> HashMap<String, String> {
+        let mut versions: HashMap<String, String> = HashMap::new();
+        for (raw_key, values) in data {
+            for entry in self.key_matches(&raw_key.to_lowercase()).flatten() {
+                let Some((re, template)) = &entry.version else { continue };
+                if versions.contains_key(&entry.name) || !names.contains(&entry.name) {
+                    continue;
+                }
+                let found = values
+                    .iter()
+                    .filter_map(|v| re.captures(v))
+                    .find_map(|caps| patterns::expand_version(template, &caps));
+                if let Some(version) = found {
+                    versions.insert(entry.name.clone(), version);
+                }
+            }
+        }
+        versions
+    }
+
+    /// Entries under the exact key, then under every regex-style key matching it.
+    fn key_matches<'a>(&'a self, key: &str) -> impl Iterator<Item = &'a Vec<DictEntry>> {
+        let by_regex: Vec<usize> = match self.key_entries.is_empty() {
+            true => Vec::new(),
+            false => self.key_set.matches(key).into_iter().collect(),
+        };
+        self.entries
+            .get(key)
+            .into_iter()
+            .chain(by_regex.into_iter().map(|idx| &self.key_entries[idx]))
+    }
+
+    fn total_patterns(&self) -> usize {
+        self.entries.values().chain(&self.key_entries).map(|v| v.len()).sum()
+    }
 }
 
-// ── Dependency resolution (implies) ──────────────────────────────────────────
+fn match_entries(entries: &[DictEntry], values: &[String], names: &mut Vec<String>) {
+    for entry in entries {
+        let matched = match &entry.value_regex {
+            None => true,
+            Some(re) => values.iter().any(|v| re.is_match(v)),
+        };
+        if matched {
+            names.push(entry.name.clone());
+        }
+    }
+}
+
+// ── Technology metadata ──────────────────────────────────────────────────────
 
-struct DependencyResolver {
-    implies: HashMap<String, Vec<String>>,
+/// Descriptive fields of a technology, kept from the fingerprint JSON.
+#[pyclass(get_all, module = "tech_detector")]
+#[derive(Clone, Default)]
+pub struct TechInfo {
+    name: String,
+    website: Option<String>,
+    icon: Option<String>,
+    description: Option<String>,
+    cpe: Option<String>,
+    pricing: Vec<String>,
+    saas: Option<bool>,
+    oss: Option<bool>,
+    cats: Vec<u32>,
 }
 
-impl DependencyResolver {
-    fn resolve(&self, names: &mut HashSet<String>) {
-        let mut queue: Vec<String> = names.iter().cloned().collect();
-        while let Some(name) = queue.pop() {
-            if let Some(implied) = self.implies.get(&name) {
-                for imp in implied {
-                    if names.insert(imp.clone()) {
-                        queue.push(imp.clone());
-                    }
-                }
-            }
+impl TechInfo {
+    fn from_json(name: &str, obj: &serde_json::Map<String, Value>) -> Self {
+        let string = |field: &str| obj.get(field).and_then(Value::as_str).map(str::to_string);
+        let flag = |field: &str| obj.get(field).and_then(Value::as_bool);
+        TechInfo {
+            name: name.to_string(),
+            website: string("website"),
+            icon: string("icon"),
+            description: string("description"),
+            cpe: string("cpe"),
+            pricing: obj.get("pricing").map(value_as_strings).unwrap_or_default(),
+            saas: flag("saas"),
+            oss: flag("oss"),
+            cats: obj
+                .get("cats")
+                .and_then(Value::as_array)
+                .map(|arr| arr.iter().filter_map(|c| c.as_u64()).map(|c| c as u32).collect())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+// ── Python input ─────────────────────────────────────────────────────────────
+
+/// A dict value from Python: either a single string or every value seen for
+/// that key (repeated `Set-Cookie`/`Link` headers, duplicate meta names).
+#[derive(FromPyObject)]
+pub enum DictValues {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// Lowercase keys and merge values, so `Link` and `link` end up in one list.
+fn to_multi_map(data: HashMap<String, DictValues>) -> HashMap<String, Vec<String>> {
+    let mut map: HashMap<String, Vec<String>> = HashMap::new();
+    for (key, values) in data {
+        let entry = map.entry(key.to_lowercase()).or_default();
+        match values {
+            DictValues::One(v) => entry.push(v),
+            DictValues::Many(vs) => entry.extend(vs),
+        }
+    }
+    map
+}
+
+/// Inputs of one detection call, with dict values merged per key and plain
+/// strings turned into parts for the text matchers.
+struct Signals {
+    html_parts: Vec<Vec<u8>>,
+    script_parts: Vec<Vec<u8>>,
+    headers: Vec<HashMap<String, Vec<String>>>,
+    cookies: Vec<HashMap<String, Vec<String>>>,
+    meta_tags: Vec<HashMap<String, Vec<String>>>,
+    dns: Option<HashMap<String, Vec<String>>>,
+    cert_issuers: Vec<Vec<u8>>,
+    robots: Vec<Vec<u8>>,
+    xhr: Vec<Vec<u8>>,
+    exchanges: Vec<HttpExchange>,
+}
+
+impl Signals {
+    /// Shared by `detect_full` and `detect`, which take the same arguments.
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        html_parts: Vec<Vec<u8>>,
+        script_parts: Vec<Vec<u8>>,
+        headers: Vec<HashMap<String, DictValues>>,
+        cookies: Vec<HashMap<String, DictValues>>,
+        meta_tags: Vec<HashMap<String, DictValues>>,
+        dns: Option<HashMap<String, DictValues>>,
+        cert_issuers: Option<Vec<String>>,
+        robots: Option<Vec<String>>,
+        xhr: Option<Vec<String>>,
+        exchanges: Option<Vec<HttpExchange>>,
+    ) -> Self {
+        Signals {
+            html_parts,
+            script_parts,
+            headers: headers.into_iter().map(to_multi_map).collect(),
+            cookies: cookies.into_iter().map(to_multi_map).collect(),
+            meta_tags: meta_tags.into_iter().map(to_multi_map).collect(),
+            dns: dns.map(to_multi_map),
+            cert_issuers: to_parts(cert_issuers),
+            robots: to_parts(robots),
+            xhr: to_parts(xhr),
+            exchanges: exchanges.unwrap_or_default(),
         }
     }
 }
 
+fn to_parts(items: Option<Vec<String>>) -> Vec<Vec<u8>> {
+    items.unwrap_or_default().into_iter().map(String::into_bytes).collect()
+}
+
 // ── PyO3 struct ───────────────────────────────────────────────────────────────
 
 #[pyclass]
@@ -142,14 +339,20 @@ pub struct TechDetector {
     headers: DictMatcher,
     cookies: DictMatcher,
     meta: DictMatcher,
+    dns: DictMatcher,
+    cert_issuer: TextMatcher,
+    robots: TextMatcher,
+    xhr: TextMatcher,
     deps: DependencyResolver,
+    info: HashMap<String, TechInfo>,
     skipped: Vec<String>,
+    dom_untranslated: Vec<(String, String, String)>,
 }
 
 // ── Builders ──────────────────────────────────────────────────────────────────
 
 fn build_text_matcher(
-    patterns_and_names: Vec<(String, String)>,
+    patterns_and_names: Vec<(TextPattern, String)>,
     case_insensitive: bool,
     chunk_size: usize,
     skipped: &mut Vec<String>,
@@ -157,17 +360,29 @@ fn build_text_matcher(
     let syntax = SyntaxConfig::new().case_insensitive(case_insensitive);
 
     let mut valid: Vec<(String, String)> = Vec::new();
-    for (pattern, name) in patterns_and_names {
+    let mut versioned: Vec<VersionPattern> = Vec::new();
+    for (text_pattern, name) in patterns_and_names {
+        let pattern = text_pattern.combined();
         let ok = DFA::builder()
             .configure(DFA::config().match_kind(MatchKind::All))
             .syntax(syntax)
             .build_many(&[pattern.as_str()])
             .is_ok();
-        if ok {
-            valid.push((pattern, name));
-        } else {
+        if !ok {
             skipped.push(format!("{name}: {pattern}"));
+            continue;
+        }
+        if let Some(template) = text_pattern.version {
+            let regexes: Result<Vec<Regex>, _> = text_pattern
+                .alternatives
+                .iter()
+                .map(|alt| RegexBuilder::new(alt).case_insensitive(case_insensitive).build())
+                .collect();
+            if let Ok(regexes) = regexes {
+                versioned.push(VersionPattern { regexes, template, name: name.clone() });
+            }
         }
+        valid.push((pattern, name));
     }
 
     let total_patterns = valid.len();
@@ -192,19 +407,21 @@ fn build_text_matcher(
         }
     }
 
-    TextMatcher { chunks, total_patterns }
+    TextMatcher { chunks, versioned, total_patterns }
 }
 
 fn build_dict_matcher(
-    entries_map: HashMap<String, Vec<(Option<String>, String)>>,
+    entries_map: HashMap<String, Vec<DictPattern>>,
     skipped: &mut Vec<String>,
 ) -> DictMatcher {
     let mut compiled: HashMap<String, Vec<DictEntry>> = HashMap::new();
+    let mut key_patterns: Vec<String> = Vec::new();
+    let mut key_entries: Vec<Vec<DictEntry>> = Vec::new();
 
     for (key, entries) in entries_map {
         let mut dict_entries = Vec::new();
-        for (pattern_opt, name) in entries {
-            let value_regex = match pattern_opt {
+        for DictPattern { value, version, name } in entries {
+            let value_regex = match value {
                 None => None,
                 Some(pat) => match Regex::new(&format!("(?i){pat}")) {
                     Ok(re) => Some(re),
@@ -214,14 +431,34 @@ fn build_dict_matcher(
                     }
                 },
             };
-            dict_entries.push(DictEntry { value_regex, name });
+            let version = version.and_then(|(pat, template)| {
+                Some((Regex::new(&format!("(?i){pat}")).ok()?, template))
+            });
+            dict_entries.push(DictEntry { value_regex, version, name });
+        }
+        if dict_entries.is_empty() {
+            continue;
         }
-        if !dict_entries.is_empty() {
+
+        if !patterns::is_key_pattern(&key) {
             compiled.insert(key, dict_entries);
+            continue;
         }
+        let key_pattern = format!("(?i)^(?:{key})$");
+        if patterns::is_unsupported(&key) || Regex::new(&key_pattern).is_err() {
+            for entry in dict_entries {
+                skipped.push(format!("{}: {key}", entry.name));
+            }
+            continue;
+        }
+        key_patterns.push(key_pattern);
+        key_entries.push(dict_entries);
     }
 
-    DictMatcher { entries: compiled }
+    // Every pattern was validated above, so building the set can't fail.
+    let key_set = RegexSet::new(&key_patterns).expect("validated key patterns");
+
+    DictMatcher { entries: compiled, key_set, key_entries }
 }
 
 // ── JSON parsing ─────────────────────────────────────────────────────────────
@@ -234,38 +471,59 @@ fn value_as_strings(v: &Value) -> Vec<String> {
     }
 }
 
-/// Strip `\;version:...` suffix from an `implies` entry to get the bare tech name.
-fn implies_name(s: &str) -> &str {
-    s.splitn(2, "\\;").next().unwrap_or(s)
+/// The technologies map, either under `apps` or at the top level.
+fn apps_object(root: &Value) -> PyResult<&serde_json::Map<String, Value>> {
+    match root {
+        Value::Object(obj) if obj.contains_key("apps") => obj["apps"]
+            .as_object()
+            .ok_or_else(|| pyo3::exceptions::PyValueError::new_err("'apps' is not an object")),
+        Value::Object(obj) => Ok(obj),
+        _ => Err(pyo3::exceptions::PyValueError::new_err("expected a JSON object")),
+    }
 }
 
 struct ParsedApps {
-    html_pats: Vec<(String, String)>,
-    script_pats: Vec<(String, String)>,
-    dom_pats: Vec<(String, String)>,
-    js_pats: Vec<(String, String)>,
-    headers_map: HashMap<String, Vec<(Option<String>, String)>>,
-    cookies_map: HashMap<String, Vec<(Option<String>, String)>>,
-    meta_map: HashMap<String, Vec<(Option<String>, String)>>,
-    implies_map: HashMap<String, Vec<String>>,
+    html_pats: Vec<(TextPattern, String)>,
+    script_pats: Vec<(TextPattern, String)>,
+    dom_pats: Vec<(TextPattern, String)>,
+    /// `(tech, selector, reason)` for dom selectors that produced no pattern
+    dom_untranslated: Vec<(String, String, String)>,
+    js_pats: Vec<(TextPattern, String)>,
+    headers_map: HashMap<String, Vec<DictPattern>>,
+    cookies_map: HashMap<String, Vec<DictPattern>>,
+    meta_map: HashMap<String, Vec<DictPattern>>,
+    dns_map: HashMap<String, Vec<DictPattern>>,
+    cert_issuer_pats: Vec<(TextPattern, String)>,
+    robots_pats: Vec<(TextPattern, String)>,
+    xhr_pats: Vec<(TextPattern, String)>,
+    implies_map: HashMap<String, Vec<Implied>>,
+    info: HashMap<String, TechInfo>,
 }
 
 fn parse_apps(apps: &serde_json::Map<String, Value>) -> ParsedApps {
-    let mut html_pats: Vec<(String, String)> = Vec::new();
-    let mut script_pats: Vec<(String, String)> = Vec::new();
-    let mut dom_pats: Vec<(String, String)> = Vec::new();
-    let mut js_pats: Vec<(String, String)> = Vec::new();
-    let mut headers_map: HashMap<String, Vec<(Option<String>, String)>> = HashMap::new();
-    let mut cookies_map: HashMap<String, Vec<(Option<String>, String)>> = HashMap::new();
-    let mut meta_map: HashMap<String, Vec<(Option<String>, String)>> = HashMap::new();
-    let mut implies_map: HashMap<String, Vec<String>> = HashMap::new();
+    let mut html_pats: Vec<(TextPattern, String)> = Vec::new();
+    let mut script_pats: Vec<(TextPattern, String)> = Vec::new();
+    let mut dom_pats: Vec<(TextPattern, String)> = Vec::new();
+    let mut dom_untranslated: Vec<(String, String, String)> = Vec::new();
+    let mut js_pats: Vec<(TextPattern, String)> = Vec::new();
+    let mut headers_map: HashMap<String, Vec<DictPattern>> = HashMap::new();
+    let mut cookies_map: HashMap<String, Vec<DictPattern>> = HashMap::new();
+    let mut meta_map: HashMap<String, Vec<DictPattern>> = HashMap::new();
+    let mut dns_map: HashMap<String, Vec<DictPattern>> = HashMap::new();
+    let mut cert_issuer_pats: Vec<(TextPattern, String)> = Vec::new();
+    let mut robots_pats: Vec<(TextPattern, String)> = Vec::new();
+    let mut xhr_pats: Vec<(TextPattern, String)> = Vec::new();
+    let mut implies_map: HashMap<String, Vec<Implied>> = HashMap::new();
+    let mut info: HashMap<String, TechInfo> = HashMap::new();
 
     for (tech_name, tech_data) in apps {
         let Some(obj) = tech_data.as_object() else { continue };
 
+        info.insert(tech_name.clone(), TechInfo::from_json(tech_name, obj));
+
         if let Some(v) = obj.get("html") {
             for raw in value_as_strings(v) {
-                if let Some(p) = patterns::preprocess_list_pattern(&raw) {
+                if let Some(p) = TextPattern::from_list(&raw) {
                     html_pats.push((p, tech_name.clone()));
                 }
             }
@@ -273,16 +531,32 @@ fn parse_apps(apps: &serde_json::Map<String, Value>) -> ParsedApps {
 
         if let Some(v) = obj.get("script") {
             for raw in value_as_strings(v) {
-                if let Some(p) = patterns::preprocess_list_pattern(&raw) {
+                if let Some(p) = TextPattern::from_list(&raw) {
                     script_pats.push((p, tech_name.clone()));
                 }
             }
         }
 
         if let Some(v) = obj.get("dom") {
-            for raw in dom::dom_to_regex(v) {
-                if let Some(p) = patterns::preprocess_list_pattern(&raw) {
-                    dom_pats.push((p, tech_name.clone()));
+            let converted = dom::dom_to_regex(v);
+            for p in converted.patterns {
+                dom_pats.push((p, tech_name.clone()));
+            }
+            for u in converted.untranslated {
+                dom_untranslated.push((tech_name.clone(), u.selector, u.reason));
+            }
+        }
+
+        for (field, target) in [
+            ("certIssuer", &mut cert_issuer_pats),
+            ("robots", &mut robots_pats),
+            ("xhr", &mut xhr_pats),
+        ] {
+            if let Some(v) = obj.get(field) {
+                for raw in value_as_strings(v) {
+                    if let Some(p) = TextPattern::from_list(&raw) {
+                        target.push((p, tech_name.clone()));
+                    }
                 }
             }
         }
@@ -291,7 +565,9 @@ fn parse_apps(apps: &serde_json::Map<String, Value>) -> ParsedApps {
             for (var_name, val) in js_obj {
                 let raw_val = val.as_str().unwrap_or("");
                 if let Some(p) = patterns::preprocess_merge_dict_pattern(var_name, raw_val) {
-                    js_pats.push((p, tech_name.clone()));
+                    let version = patterns::version_template(raw_val);
+                    let pattern = TextPattern { alternatives: vec![p], version };
+                    js_pats.push((pattern, tech_name.clone()));
                 }
             }
         }
@@ -300,31 +576,32 @@ fn parse_apps(apps: &serde_json::Map<String, Value>) -> ParsedApps {
             ("headers", &mut headers_map),
             ("cookies", &mut cookies_map),
             ("meta", &mut meta_map),
+            ("dns", &mut dns_map),
         ] {
             if let Some(Value::Object(dict)) = obj.get(field) {
                 for (key, val) in dict {
-                    let lower_key = key.to_lowercase();
-                    let raw_val = match val {
-                        Value::String(s) => s.as_str(),
-                        Value::Array(arr) => {
-                            arr.iter().find_map(|v| v.as_str()).unwrap_or("")
-                        }
-                        _ => "",
-                    };
-                    let pattern_opt = patterns::preprocess_dict_value(raw_val);
-                    target_map
-                        .entry(lower_key)
-                        .or_default()
-                        .push((pattern_opt, tech_name.clone()));
+                    let mut raw_vals = value_as_strings(val);
+                    if raw_vals.is_empty() {
+                        raw_vals.push(String::new());
+                    }
+                    let key_entries = target_map.entry(key.to_lowercase()).or_default();
+                    for raw_val in raw_vals {
+                        key_entries.push(DictPattern {
+                            value: patterns::preprocess_dict_value(&raw_val),
+                            version: patterns::dict_version(&raw_val),
+                            name: tech_name.clone(),
+                        });
+                    }
                 }
             }
         }
 
         if let Some(v) = obj.get("implies") {
-            let implied: Vec<String> = value_as_strings(v)
-                .into_iter()
-                .map(|s| implies_name(&s).to_string())
-                .filter(|s| !s.is_empty())
+            let implied: Vec<Implied> = value_as_strings(v)
+                .iter()
+                .map(|s| deps::parse_implies(s))
+                .filter(|(name, _)| !name.is_empty())
+                .map(|(name, confidence)| Implied { name: name.to_string(), confidence })
                 .collect();
             if !implied.is_empty() {
                 implies_map.insert(tech_name.clone(), implied);
@@ -336,11 +613,17 @@ fn parse_apps(apps: &serde_json::Map<String, Value>) -> ParsedApps {
         html_pats,
         script_pats,
         dom_pats,
+        dom_untranslated,
         js_pats,
         headers_map,
         cookies_map,
         meta_map,
+        dns_map,
+        cert_issuer_pats,
+        robots_pats,
+        xhr_pats,
         implies_map,
+        info,
     }
 }
 
@@ -354,24 +637,24 @@ impl TechDetector {
         let root: Value = serde_json::from_slice(json_data)
             .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
 
-        let apps = match &root {
-            Value::Object(obj) if obj.contains_key("apps") => obj["apps"]
-                .as_object()
-                .ok_or_else(|| pyo3::exceptions::PyValueError::new_err("'apps' is not an object"))?,
-            Value::Object(obj) => obj,
-            _ => return Err(pyo3::exceptions::PyValueError::new_err("expected a JSON object")),
-        };
+        let apps = apps_object(&root)?;
 
         let mut skipped: Vec<String> = Vec::new();
         let ParsedApps {
             html_pats,
             script_pats,
             dom_pats,
+            dom_untranslated,
             js_pats,
             headers_map,
             cookies_map,
             meta_map,
+            dns_map,
+            cert_issuer_pats,
+            robots_pats,
+            xhr_pats,
             implies_map,
+            info,
         } = parse_apps(apps);
 
         let html = build_text_matcher(html_pats, true, chunk_size, &mut skipped);
@@ -381,44 +664,80 @@ impl TechDetector {
         let headers = build_dict_matcher(headers_map, &mut skipped);
         let cookies = build_dict_matcher(cookies_map, &mut skipped);
         let meta = build_dict_matcher(meta_map, &mut skipped);
-        let deps = DependencyResolver { implies: implies_map };
-
-        Ok(TechDetector { html, script, dom, js, headers, cookies, meta, deps, skipped })
+        let dns = build_dict_matcher(dns_map, &mut skipped);
+        let cert_issuer = build_text_matcher(cert_issuer_pats, true, chunk_size, &mut skipped);
+        let robots = build_text_matcher(robots_pats, true, chunk_size, &mut skipped);
+        let xhr = build_text_matcher(xhr_pats, true, chunk_size, &mut skipped);
+        let known = info.keys().cloned().collect();
+        let deps = DependencyResolver { implies: implies_map, known };
+
+        Ok(TechDetector {
+            html,
+            script,
+            dom,
+            js,
+            headers,
+            cookies,
+            meta,
+            dns,
+            cert_issuer,
+            robots,
+            xhr,
+            deps,
+            info,
+            skipped,
+            dom_untranslated,
+        })
     }
 
     /// Full detection: body text + headers/cookies/meta + dependency resolution.
     ///
     /// - `html_parts`   — page bodies split on `</div>`, all pages merged
     /// - `script_parts` — script-tag contents split on blank lines, all pages merged
-    /// - `headers`      — one dict per page
-    /// - `cookies`      — one dict per page
-    /// - `meta_tags`    — flat list of single-key dicts from all pages
-    #[pyo3(signature = (html_parts, script_parts, headers, cookies, meta_tags, include_dependencies=true))]
+    /// - `headers`      — one dict per page; values are a string or a list of strings
+    /// - `cookies`      — one dict per page; values are a string or a list of strings
+    /// - `meta_tags`    — flat list of dicts from all pages, values as above
+    ///
+    /// Optional signals the caller collected outside the page itself:
+    ///
+    /// - `dns`          — record type (`TXT`, `MX`, `NS`, ...) → record values
+    /// - `cert_issuers` — TLS certificate issuer names
+    /// - `robots`       — robots.txt bodies
+    /// - `xhr`          — hostnames/URLs of XHR requests made by the page
+    /// - `exchanges`    — `HttpExchange`s whose headers and cookies are matched on
+    ///   every hop; see `detect_http` for per-hop results
+    #[pyo3(signature = (
+        html_parts, script_parts, headers, cookies, meta_tags, include_dependencies=true,
+        *, dns=None, cert_issuers=None, robots=None, xhr=None, exchanges=None
+    ))]
+    #[allow(clippy::too_many_arguments)]
     pub fn detect_full(
         &self,
         html_parts: Vec<Vec<u8>>,
         script_parts: Vec<Vec<u8>>,
-        headers: Vec<HashMap<String, String>>,
-        cookies: Vec<HashMap<String, String>>,
-        meta_tags: Vec<HashMap<String, String>>,
+        headers: Vec<HashMap<String, DictValues>>,
+        cookies: Vec<HashMap<String, DictValues>>,
+        meta_tags: Vec<HashMap<String, DictValues>>,
         include_dependencies: bool,
+        dns: Option<HashMap<String, DictValues>>,
+        cert_issuers: Option<Vec<String>>,
+        robots: Option<Vec<String>>,
+        xhr: Option<Vec<String>>,
+        exchanges: Option<Vec<HttpExchange>>,
     ) -> Vec<String> {
-        let mut names: HashSet<String> = HashSet::new();
-
-        names.extend(self.html.match_parts(&html_parts));
-        names.extend(self.dom.match_parts(&html_parts));
-        names.extend(self.script.match_parts(&script_parts));
-        names.extend(self.js.match_parts(&script_parts));
-
-        for h in &headers {
-            names.extend(self.headers.match_dict(h));
-        }
-        for c in &cookies {
-            names.extend(self.cookies.match_dict(c));
-        }
-        for m in &meta_tags {
-            names.extend(self.meta.match_dict(m));
-        }
+        let signals = Signals::new(
+            html_parts,
+            script_parts,
+            headers,
+            cookies,
+            meta_tags,
+            dns,
+            cert_issuers,
+            robots,
+            xhr,
+            exchanges,
+        );
+        let mut names: HashSet<String> = self.match_signals(&signals).into_keys().collect();
 
         if include_dependencies {
             self.deps.resolve(&mut names);
@@ -427,6 +746,48 @@ impl TechDetector {
         names.into_iter().collect()
     }
 
+    /// Same inputs as `detect_full`, returned as a `DetectionReport` with versions,
+    /// the fields each technology matched in and why implied ones were added.
+    #[pyo3(signature = (
+        html_parts, script_parts, headers, cookies, meta_tags, include_dependencies=true,
+        *, dns=None, cert_issuers=None, robots=None, xhr=None, exchanges=None
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    pub fn detect(
+        &self,
+        html_parts: Vec<Vec<u8>>,
+        script_parts: Vec<Vec<u8>>,
+        headers: Vec<HashMap<String, DictValues>>,
+        cookies: Vec<HashMap<String, DictValues>>,
+        meta_tags: Vec<HashMap<String, DictValues>>,
+        include_dependencies: bool,
+        dns: Option<HashMap<String, DictValues>>,
+        cert_issuers: Option<Vec<String>>,
+        robots: Option<Vec<String>>,
+        xhr: Option<Vec<String>>,
+        exchanges: Option<Vec<HttpExchange>>,
+    ) -> DetectionReport {
+        let signals = Signals::new(
+            html_parts,
+            script_parts,
+            headers,
+            cookies,
+            meta_tags,
+            dns,
+            cert_issuers,
+            robots,
+            xhr,
+            exchanges,
+        );
+        self.build_report(&signals, include_dependencies)
+    }
+
+    /// Header and cookie detections for every hop of every exchange, recording
+    /// which hop matched. CDN and WAF signals often only show up on a redirect.
+    pub fn detect_http(&self, exchanges: Vec<HttpExchange>) -> Vec<HopMatch> {
+        self.match_exchanges(&exchanges)
+    }
+
     /// Resolve implies dependencies for an already-detected set of tech names.
     /// Useful when you need to filter detections before expanding dependencies.
     pub fn resolve_dependencies(&self, names: Vec<String>) -> Vec<String> {
@@ -435,73 +796,292 @@ impl TechDetector {
         set.into_iter().collect()
     }
 
+    /// `implies` edges of one technology as `(implied name, confidence)`.
+    pub fn implies_edges(&self, name: &str) -> Vec<(String, u8)> {
+        self.deps
+            .implies
+            .get(name)
+            .map(|implied| implied.iter().map(|i| (i.name.clone(), i.confidence)).collect())
+            .unwrap_or_default()
+    }
+
+    /// `(source, target)` implies edges whose target isn't in the database.
+    pub fn dangling_implies(&self) -> Vec<(String, String)> {
+        self.deps.dangling()
+    }
+
+    /// Cycles in the implies graph; empty when the graph is acyclic.
+    pub fn implies_cycles(&self) -> Vec<Vec<String>> {
+        self.deps.cycles()
+    }
+
+    /// Explain why `name` was added by dependency resolution: the shortest
+    /// implies path from one of the `detected` technologies to `name`.
+    pub fn implication_chain(&self, detected: Vec<String>, name: &str) -> Option<Vec<String>> {
+        self.deps.chain(&detected, name)
+    }
+
     /// Low-level: match a single text key against a list of byte parts.
     pub fn detect_text_key(&self, key: &str, parts: Vec<Vec<u8>>) -> PyResult<Vec<String>> {
-        let matcher = match key {
-            "html" => &self.html,
-            "script" => &self.script,
-            "dom" => &self.dom,
-            "js" => &self.js,
-            other => {
-                return Err(pyo3::exceptions::PyValueError::new_err(format!(
-                    "unknown key: {other}"
-                )))
-            }
-        };
-        Ok(matcher.match_parts(&parts))
+        Ok(self.text_matcher(key)?.match_parts(&parts))
+    }
+
+    /// Low-level: versions of the technologies a single text key matches,
+    /// for patterns that declare a `\;version:` template.
+    pub fn detect_versions(
+        &self,
+        key: &str,
+        parts: Vec<Vec<u8>>,
+    ) -> PyResult<HashMap<String, String>> {
+        let matcher = self.text_matcher(key)?;
+        let names: HashSet<String> = matcher.match_parts(&parts).into_iter().collect();
+        Ok(matcher.match_versions(&parts, &names))
     }
 
     /// Low-level: match a single dict key against one Python dict.
+    /// Values may be a string or a list of strings.
     pub fn detect_dict_key(&self, key: &str, data: &Bound<'_, PyDict>) -> PyResult<Vec<String>> {
         let matcher = match key {
             "headers" => &self.headers,
             "cookies" => &self.cookies,
             "meta" => &self.meta,
+            "dns" => &self.dns,
             other => {
                 return Err(pyo3::exceptions::PyValueError::new_err(format!(
                     "unknown key: {other}"
                 )))
             }
         };
-        let map: HashMap<String, String> = data
+        let map: HashMap<String, DictValues> = data
             .iter()
             .filter_map(|(k, v)| {
-                Some((k.extract::<String>().ok()?, v.extract::<String>().ok()?))
+                Some((k.extract::<String>().ok()?, v.extract::<DictValues>().ok()?))
             })
             .collect();
-        Ok(matcher.match_dict(&map))
+        let mut names = matcher.match_dict(&to_multi_map(map));
+        names.sort();
+        names.dedup();
+        Ok(names)
+    }
+
+    /// Metadata (website, icon, description, CPE, pricing, ...) for one technology.
+    pub fn technology_info(&self, name: &str) -> Option<TechInfo> {
+        self.info.get(name).cloned()
+    }
+
+    /// Metadata for several technologies at once; unknown names are left out.
+    pub fn technologies_info(&self, names: Vec<String>) -> HashMap<String, TechInfo> {
+        names
+            .into_iter()
+            .filter_map(|name| {
+                let info = self.info.get(&name)?.clone();
+                Some((name, info))
+            })
+            .collect()
     }
 
     pub fn pattern_counts(&self) -> HashMap<String, usize> {
-        let mut counts = HashMap::new();
-        counts.insert("html".into(), self.html.total_patterns);
-        counts.insert("script".into(), self.script.total_patterns);
-        counts.insert("dom".into(), self.dom.total_patterns);
-        counts.insert("js".into(), self.js.total_patterns);
-        counts.insert(
-            "headers".into(),
-            self.headers.entries.values().map(|v| v.len()).sum(),
-        );
-        counts.insert(
-            "cookies".into(),
-            self.cookies.entries.values().map(|v| v.len()).sum(),
-        );
-        counts.insert(
-            "meta".into(),
-            self.meta.entries.values().map(|v| v.len()).sum(),
-        );
-        counts.insert("implies".into(), self.deps.implies.len());
-        counts
+        self.pattern_stats().as_dict()
+    }
+
+    pub fn pattern_stats(&self) -> PatternStats {
+        PatternStats {
+            html: self.html.total_patterns,
+            script: self.script.total_patterns,
+            dom: self.dom.total_patterns,
+            js: self.js.total_patterns,
+            headers: self.headers.total_patterns(),
+            cookies: self.cookies.total_patterns(),
+            meta: self.meta.total_patterns(),
+            dns: self.dns.total_patterns(),
+            cert_issuer: self.cert_issuer.total_patterns,
+            robots: self.robots.total_patterns,
+            xhr: self.xhr.total_patterns,
+            implies: self.deps.implies.len(),
+            technologies: self.info.len(),
+            skipped: self.skipped.len(),
+            untranslated: self.dom_untranslated.len(),
+        }
     }
 
     pub fn skipped_patterns(&self) -> Vec<String> {
         self.skipped.clone()
     }
+
+    /// `(technology, selector, reason)` for every `dom` selector or check that
+    /// couldn't be translated into a pattern.
+    pub fn untranslated_selectors(&self) -> Vec<(String, String, String)> {
+        self.dom_untranslated.clone()
+    }
+}
+
+impl TechDetector {
+    /// Name → fields it matched in, before dependency resolution.
+    fn match_signals(&self, signals: &Signals) -> HashMap<String, Vec<&'static str>> {
+        let mut sources: HashMap<String, Vec<&'static str>> = HashMap::new();
+        let mut add = |names: Vec<String>, field: &'static str| {
+            for name in names {
+                let fields = sources.entry(name).or_default();
+                if !fields.contains(&field) {
+                    fields.push(field);
+                }
+            }
+        };
+
+        for (field, matcher, parts) in self.text_fields(signals) {
+            add(matcher.match_parts(parts), field);
+        }
+        for (field, matcher, maps) in [
+            ("headers", &self.headers, &signals.headers),
+            ("cookies", &self.cookies, &signals.cookies),
+            ("meta", &self.meta, &signals.meta_tags),
+        ] {
+            for map in maps {
+                add(matcher.match_dict(map), field);
+            }
+        }
+        if let Some(records) = &signals.dns {
+            add(self.dns.match_dict(records), "dns");
+        }
+        for m in self.match_exchanges(&signals.exchanges) {
+            // `HopMatch::field` is owned for Python; only these two occur.
+            let field = if m.field == "cookies" { "cookies" } else { "headers" };
+            add(vec![m.name], field);
+        }
+
+        for fields in sources.values_mut() {
+            fields.sort();
+        }
+        sources
+    }
+
+    fn text_fields<'a>(
+        &'a self,
+        signals: &'a Signals,
+    ) -> [(&'static str, &'a TextMatcher, &'a [Vec<u8>]); 7] {
+        [
+            ("html", &self.html, &signals.html_parts),
+            ("dom", &self.dom, &signals.html_parts),
+            ("script", &self.script, &signals.script_parts),
+            ("js", &self.js, &signals.script_parts),
+            ("certIssuer", &self.cert_issuer, &signals.cert_issuers),
+            ("robots", &self.robots, &signals.robots),
+            ("xhr", &self.xhr, &signals.xhr),
+        ]
+    }
+
+    fn build_report(&self, signals: &Signals, include_dependencies: bool) -> DetectionReport {
+        let sources = self.match_signals(signals);
+        let direct: HashSet<String> = sources.keys().cloned().collect();
+
+        let mut versions: HashMap<String, String> = HashMap::new();
+        for (_, matcher, parts) in self.text_fields(signals) {
+            for (name, version) in matcher.match_versions(parts, &direct) {
+                versions.entry(name).or_insert(version);
+            }
+        }
+        let hop_maps: Vec<(&DictMatcher, HashMap<String, Vec<String>>)> = signals
+            .exchanges
+            .iter()
+            .flat_map(|exchange| exchange.hops())
+            .flat_map(|hop| [(&self.headers, hop.header_map()), (&self.cookies, hop.cookie_map())])
+            .collect();
+        let dict_fields = [
+            (&self.headers, &signals.headers),
+            (&self.cookies, &signals.cookies),
+            (&self.meta, &signals.meta_tags),
+        ]
+        .into_iter()
+        .flat_map(|(matcher, maps)| maps.iter().map(move |map| (matcher, map)))
+        .chain(signals.dns.iter().map(|records| (&self.dns, records)))
+        .chain(hop_maps.iter().map(|(matcher, map)| (*matcher, map)));
+        for (matcher, map) in dict_fields {
+            for (name, version) in matcher.match_versions(map, &direct) {
+                versions.entry(name).or_insert(version);
+            }
+        }
+
+        let mut names = direct.clone();
+        if include_dependencies {
+            self.deps.resolve(&mut names);
+        }
+        let mut direct_sorted: Vec<String> = direct.into_iter().collect();
+        direct_sorted.sort();
+
+        let mut detections: Vec<Detection> = names
+            .into_iter()
+            .map(|name| {
+                let (fields, chain) = match sources.get(&name) {
+                    Some(fields) => (fields.iter().map(|f| f.to_string()).collect(), Vec::new()),
+                    None => {
+                        let chain = self.deps.chain(&direct_sorted, &name).unwrap_or_default();
+                        (Vec::new(), chain)
+                    }
+                };
+                Detection {
+                    version: versions.remove(&name),
+                    implied: fields.is_empty(),
+                    sources: fields,
+                    implication_chain: chain,
+                    name,
+                }
+            })
+            .collect();
+        detections.sort_by(|a, b| a.name.cmp(&b.name));
+        DetectionReport { detections }
+    }
+
+    fn match_exchanges(&self, exchanges: &[HttpExchange]) -> Vec<HopMatch> {
+        let mut matches = Vec::new();
+        for (exchange_idx, exchange) in exchanges.iter().enumerate() {
+            for (hop_idx, hop) in exchange.hops().iter().enumerate() {
+                for (field, matcher, data) in [
+                    ("headers", &self.headers, hop.header_map()),
+                    ("cookies", &self.cookies, hop.cookie_map()),
+                ] {
+                    let mut names = matcher.match_dict(&data);
+                    names.sort();
+                    names.dedup();
+                    matches.extend(
+                        names
+                            .into_iter()
+                            .map(|name| HopMatch::new(name, field, exchange_idx, hop_idx, hop)),
+                    );
+                }
+            }
+        }
+        matches
+    }
+
+    fn text_matcher(&self, key: &str) -> PyResult<&TextMatcher> {
+        match key {
+            "html" => Ok(&self.html),
+            "script" => Ok(&self.script),
+            "dom" => Ok(&self.dom),
+            "js" => Ok(&self.js),
+            "certIssuer" => Ok(&self.cert_issuer),
+            "robots" => Ok(&self.robots),
+            "xhr" => Ok(&self.xhr),
+            other => Err(pyo3::exceptions::PyValueError::new_err(format!("unknown key: {other}"))),
+        }
+    }
 }
 
 #[pymodule]
 fn tech_detector(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<TechDetector>()?;
+    m.add_class::<TechInfo>()?;
+    m.add_class::<HttpHop>()?;
+    m.add_class::<HttpExchange>()?;
+    m.add_class::<HopMatch>()?;
+    m.add_class::<Detection>()?;
+    m.add_class::<DetectionReport>()?;
+    m.add_class::<PatternStats>()?;
+    m.add_class::<DetectionDiff>()?;
+    m.add_class::<VersionChange>()?;
+    m.add_class::<Diagnostic>()?;
+    m.add_function(wrap_pyfunction!(diff::diff_detections, m)?)?;
+    m.add_function(wrap_pyfunction!(lint::lint_technologies, m)?)?;
     Ok(())
 }
 
@@ -516,17 +1096,37 @@ mod tests {
             "React": {
                 "html": "react",
                 "js": {"React.version": "([0-9.]+)\\;version:\\1"},
-                "implies": "Webpack"
+                "implies": ["Webpack", "Node.js\\;confidence:50"]
             },
             "Webpack": {
-                "script": "webpack"
+                "script": "webpack",
+                "implies": "Babel",
+                "website": "https://webpack.js.org",
+                "cats": [19],
+                "oss": true
             },
             "jQuery": {
                 "script": "jquery[.\\-]([\\d.]*\\d)[/\\w.]*\\.js\\;version:\\1",
                 "js": {"jQuery.fn.jquery": "([\\d.]+)\\;version:\\1"},
                 "headers": {"x-powered-by": "jquery"}
             },
+            "Shopify": {
+                "headers": {"link": ["cdn\\.shopify\\.com", "shopifycloud"]},
+                "robots": "Shopify-Challenge"
+            },
+            "Google Workspace": {
+                "dns": {"MX": ["aspmx\\.l\\.google\\.com", "googlemail\\.com"], "TXT": "google-site-verification"}
+            },
+            "Cloudflare": {
+                "certIssuer": "Cloudflare",
+                "xhr": "cdnjs\\.cloudflare\\.com"
+            },
             "WordPress": {
+                "website": "https://wordpress.org",
+                "description": "WordPress is a free and open-source content management system.",
+                "cpe": "cpe:2.3:a:wordpress:wordpress:*:*:*:*:*:*:*:*",
+                "pricing": ["low", "recurring"],
+                "saas": false,
                 "html": "wp-content",
                 "meta": {"generator": "WordPress ([\\d.]+)\\;version:\\1"},
                 "cookies": {"wordpress_[a-z0-9_]+": ""}
@@ -599,11 +1199,76 @@ mod tests {
             vec![],
             vec![],
             true,
+            None,
+            None,
+            None,
+            None,
+            None,
         );
         assert!(names.contains(&"React".to_string()), "expected React, got {names:?}");
         assert!(names.contains(&"Webpack".to_string()), "expected Webpack (implied), got {names:?}");
     }
 
+    #[test]
+    fn test_detect_report() {
+        let d = detector();
+        let headers: HashMap<String, DictValues> =
+            [("X-Powered-By".to_string(), DictValues::One("jQuery".to_string()))].into();
+        let report = d.detect(
+            vec![b"<div class=react-root>".to_vec()],
+            vec![b"<script src=/js/jquery-3.6.0.min.js>".to_vec()],
+            vec![headers],
+            vec![],
+            vec![],
+            true,
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+        assert_eq!(report.names(), vec!["Babel", "Node.js", "React", "Webpack", "jQuery"]);
+
+        let jquery = report.get("jQuery").unwrap();
+        assert_eq!(jquery.version.as_deref(), Some("3.6.0"));
+        assert_eq!(jquery.sources, vec!["headers", "script"]);
+        assert!(!jquery.implied);
+
+        let babel = report.get("Babel").unwrap();
+        assert!(babel.implied && babel.sources.is_empty());
+        assert_eq!(babel.implication_chain, vec!["React", "Webpack", "Babel"]);
+
+        let versions = report.versions();
+        assert_eq!(versions.len(), 2);
+        assert_eq!(versions["React"], None);
+
+        let previous = DetectionReport { detections: report.direct() };
+        let diff = report.diff(&previous);
+        assert!(diff.added.is_empty() && diff.removed.is_empty());
+        assert_eq!(diff.implied_added, vec!["Babel", "Node.js", "Webpack"]);
+    }
+
+    #[test]
+    fn test_detect_report_dict_versions() {
+        let d = detector();
+        let meta: HashMap<String, DictValues> =
+            [("generator".to_string(), DictValues::One("WordPress 6.4.2".to_string()))].into();
+        let report =
+            d.detect(vec![], vec![], vec![], vec![], vec![meta], true, None, None, None, None, None);
+        let wordpress = report.get("WordPress").unwrap();
+        assert_eq!(wordpress.version.as_deref(), Some("6.4.2"));
+        assert_eq!(wordpress.sources, vec!["meta"]);
+    }
+
+    #[test]
+    fn test_pattern_stats() {
+        let stats = detector().pattern_stats();
+        assert_eq!(stats.technologies, 7);
+        assert_eq!(stats.implies, 2);
+        assert_eq!(stats.dns, 3);
+        assert_eq!(stats.total(), stats.as_dict().values().sum::<usize>() - stats.implies);
+    }
+
     #[test]
     fn test_detect_full_no_dependencies() {
         let d = detector();
@@ -614,11 +1279,417 @@ mod tests {
             vec![],
             vec![],
             false,
+            None,
+            None,
+            None,
+            None,
+            None,
         );
         assert!(names.contains(&"React".to_string()));
         assert!(!names.contains(&"Webpack".to_string()), "Webpack should not appear without deps");
     }
 
+    #[test]
+    fn test_cookie_key_pattern() {
+        let d = detector();
+        let cookies =
+            HashMap::from([("wordpress_logged_in_abc123".to_string(), vec!["1".to_string()])]);
+        let names = d.cookies.match_dict(&cookies);
+        assert_eq!(names, vec!["WordPress".to_string()]);
+
+        let cookies = HashMap::from([("wordpress".to_string(), vec!["1".to_string()])]);
+        assert!(d.cookies.match_dict(&cookies).is_empty());
+    }
+
+    #[test]
+    fn test_header_exact_key_case_insensitive() {
+        let d = detector();
+        let headers = HashMap::from([("X-Powered-By".to_string(), vec!["jQuery".to_string()])]);
+        assert_eq!(d.headers.match_dict(&headers), vec!["jQuery".to_string()]);
+    }
+
+    #[test]
+    fn test_header_array_patterns_and_repeated_values() {
+        let d = detector();
+        // Only the second `link` value matches, and only the second pattern does.
+        let headers = HashMap::from([(
+            "link".to_string(),
+            vec![
+                "<https://fonts.googleapis.com>; rel=preconnect".to_string(),
+                "<https://x.shopifycloud.com/a.js>; rel=preload".to_string(),
+            ],
+        )]);
+        assert_eq!(d.headers.match_dict(&headers), vec!["Shopify".to_string()]);
+        assert_eq!(*d.pattern_counts().get("headers").unwrap(), 3);
+    }
+
+    #[test]
+    fn test_to_multi_map_merges_key_case() {
+        let map = to_multi_map(HashMap::from([
+            ("Link".to_string(), DictValues::One("a".to_string())),
+            ("link".to_string(), DictValues::Many(vec!["b".to_string()])),
+        ]));
+        let mut values = map["link"].clone();
+        values.sort();
+        assert_eq!(values, vec!["a".to_string(), "b".to_string()]);
+    }
+
+    #[test]
+    fn test_dns_records() {
+        let d = detector();
+        let records = HashMap::from([(
+            "MX".to_string(),
+            vec!["10 mail.example.com".to_string(), "1 ASPMX.L.GOOGLE.COM".to_string()],
+        )]);
+        assert_eq!(d.dns.match_dict(&records), vec!["Google Workspace".to_string()]);
+    }
+
+    #[test]
+    fn test_detect_full_external_signals() {
+        let d = detector();
+        let names = d.detect_full(
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+            false,
+            None,
+            Some(vec!["Cloudflare Inc ECC CA-3".to_string()]),
+            Some(vec!["User-agent: *\n# Shopify-Challenge: hello".to_string()]),
+            None,
+            None,
+        );
+        assert!(names.contains(&"Cloudflare".to_string()), "got {names:?}");
+        assert!(names.contains(&"Shopify".to_string()), "got {names:?}");
+
+        let names =
+            d.detect_text_key("xhr", vec![b"https://cdnjs.cloudflare.com/ajax/libs".to_vec()]);
+        assert_eq!(names.unwrap(), vec!["Cloudflare".to_string()]);
+    }
+
+    #[test]
+    fn test_technology_info() {
+        let d = detector();
+        let wp = d.technology_info("WordPress").unwrap();
+        assert_eq!(wp.cpe.as_deref(), Some("cpe:2.3:a:wordpress:wordpress:*:*:*:*:*:*:*:*"));
+        assert_eq!(wp.pricing, vec!["low".to_string(), "recurring".to_string()]);
+        assert_eq!(wp.saas, Some(false));
+        assert!(d.technology_info("Unknown").is_none());
+
+        let infos = d.technologies_info(vec!["Webpack".to_string(), "Unknown".to_string()]);
+        assert_eq!(infos.len(), 1);
+        assert_eq!(infos["Webpack"].cats, vec![19]);
+        assert_eq!(infos["Webpack"].oss, Some(true));
+    }
+
+    #[test]
+    fn test_implies_graph() {
+        let d = detector();
+        assert_eq!(
+            d.implies_edges("React"),
+            vec![("Webpack".to_string(), 100), ("Node.js".to_string(), 50)]
+        );
+        assert_eq!(
+            d.dangling_implies(),
+            vec![
+                ("React".to_string(), "Node.js".to_string()),
+                ("Webpack".to_string(), "Babel".to_string()),
+            ]
+        );
+        assert!(d.implies_cycles().is_empty());
+        assert_eq!(
+            d.implication_chain(vec!["jQuery".to_string(), "React".to_string()], "Babel"),
+            Some(vec!["React".to_string(), "Webpack".to_string(), "Babel".to_string()])
+        );
+        assert_eq!(d.implication_chain(vec!["jQuery".to_string()], "Babel"), None);
+    }
+
+    #[test]
+    fn test_implies_cycles() {
+        let d = TechDetector::new(
+            br#"{"A": {"implies": "B"}, "B": {"implies": ["C", "D"]}, "C": {"implies": "A"}, "D": {}}"#,
+            8,
+        )
+        .unwrap();
+        assert_eq!(
+            d.implies_cycles(),
+            vec![vec!["A".to_string(), "B".to_string(), "C".to_string()]]
+        );
+        assert_eq!(deps::parse_implies("PHP\\;version:7\\;confidence:75"), ("PHP", 75));
+    }
+
+    const DOM_JSON: &str = r##"{
+        "Next.js": {"dom": {"#__next": {"exists": ""}}},
+        "jQuery": {"dom": {"script[src*='jquery']": {"attributes": {"src": "jquery-([\\d.]+)\\.min\\.js\\;version:\\1"}}}},
+        "Shop": {"dom": {"footer span.version": {"text": "^v([\\d.]+)$\\;version:\\1"}}},
+        "Hugo": {"dom": {"meta[name='generator']": {"properties": {"content": "Hugo ([\\d.]+)\\;version:\\1"}}}},
+        "Vue.js": {"dom": {"#app": {"properties": {"__vue__": ""}}}}
+    }"##;
+
+    #[test]
+    fn test_dom_dict_exists() {
+        let d = TechDetector::new(DOM_JSON.as_bytes(), 8).unwrap();
+        let names = d.detect_text_key("dom", vec![b"<body><div id=\"__next\">".to_vec()]).unwrap();
+        assert_eq!(names, vec!["Next.js".to_string()]);
+        // minified markup drops the quotes around attribute values
+        for html in [
+            &b"<div id=__next></div>"[..],
+            b"<div id=__next>Loading",
+            b"<div id=__next class=x>",
+            b"<div class=x id=__next>",
+            b"<div id=__next/>",
+        ] {
+            let names = d.detect_text_key("dom", vec![html.to_vec()]).unwrap();
+            assert_eq!(names, vec!["Next.js".to_string()], "{}", String::from_utf8_lossy(html));
+        }
+        for html in [&b"<div id=\"__nextjs\">"[..], b"<div id=__nextjs>", b"<div id=__next-x>"] {
+            let names = d.detect_text_key("dom", vec![html.to_vec()]).unwrap();
+            assert!(names.is_empty(), "unexpected: {names:?}");
+        }
+    }
+
+    #[test]
+    fn test_dom_dict_versions() {
+        let d = TechDetector::new(DOM_JSON.as_bytes(), 8).unwrap();
+        let html = br#"<script type="text/javascript" src="/js/jquery-3.6.0.min.js"></script>
+            <span class="small version">v2.4.1</span>
+            <meta content="Hugo 0.110.0" name="generator">"#;
+        let versions = d.detect_versions("dom", vec![html.to_vec()]).unwrap();
+        assert_eq!(versions.get("jQuery").map(String::as_str), Some("3.6.0"));
+        assert_eq!(versions.get("Shop").map(String::as_str), Some("2.4.1"));
+        assert_eq!(versions.get("Hugo").map(String::as_str), Some("0.110.0"));
+
+        let html = br#"<script src=/js/jquery-3.7.1.min.js></script>
+            <footer><span class=version>v2.5.0</span>
+            <meta name=generator content="Hugo 0.120.4">"#;
+        let versions = d.detect_versions("dom", vec![html.to_vec()]).unwrap();
+        assert_eq!(versions.get("jQuery").map(String::as_str), Some("3.7.1"));
+        assert_eq!(versions.get("Shop").map(String::as_str), Some("2.5.0"));
+        assert_eq!(versions.get("Hugo").map(String::as_str), Some("0.120.4"));
+    }
+
+    #[test]
+    fn test_dom_dict_runtime_property_dropped() {
+        let d = TechDetector::new(DOM_JSON.as_bytes(), 8).unwrap();
+        assert_eq!(*d.pattern_counts().get("dom").unwrap(), 4);
+        let names = d.detect_text_key("dom", vec![b"<div id=\"app\">".to_vec()]).unwrap();
+        assert!(names.is_empty(), "unexpected: {names:?}");
+    }
+
+    #[test]
+    fn test_dom_list_any_tag_and_attribute() {
+        let d = TechDetector::new(
+            br#"{"Astro": {"dom": ["astro-island[component-url]", "meta[property='astro:site'], span.x"]}}"#,
+            8,
+        )
+        .unwrap();
+        assert_eq!(*d.pattern_counts().get("dom").unwrap(), 3);
+        for html in [
+            &b"<astro-island uid=\"1\" component-url=\"/a.js\">"[..],
+            b"<meta content=\"x\" property=\"astro:site\">",
+            b"<span class=\"y x\">",
+        ] {
+            let names = d.detect_text_key("dom", vec![html.to_vec()]).unwrap();
+            assert_eq!(names, vec!["Astro".to_string()]);
+        }
+        let names = d.detect_text_key("dom", vec![b"<span class=\"xy\">".to_vec()]).unwrap();
+        assert!(names.is_empty(), "unexpected: {names:?}");
+    }
+
+    #[test]
+    fn test_untranslated_selectors() {
+        let d = TechDetector::new(DOM_JSON.as_bytes(), 8).unwrap();
+        assert_eq!(
+            d.untranslated_selectors(),
+            vec![(
+                "Vue.js".to_string(),
+                "#app".to_string(),
+                "property `__vue__` only exists at runtime".to_string()
+            )]
+        );
+
+        let d = TechDetector::new(br#"{"X": {"dom": ["a:hover", "div[data-x", "*"]}}"#, 8).unwrap();
+        let reasons: Vec<String> = d.untranslated_selectors().into_iter().map(|u| u.2).collect();
+        assert_eq!(
+            reasons,
+            vec![
+                "pseudo-class `:hover` can't be checked in markup".to_string(),
+                "unclosed `[`".to_string(),
+                "selector matches any element".to_string(),
+            ]
+        );
+
+        let json = br##"{"X": {"dom": {"#app": "", "ul": {"exists": ""}}}, "Y": {"dom": ["div", 1]}}"##;
+        let d = TechDetector::new(json, 8).unwrap();
+        let mut untranslated = d.untranslated_selectors();
+        untranslated.sort();
+        assert_eq!(
+            untranslated,
+            vec![
+                ("X".to_string(), "#app".to_string(), "checks are not an object".to_string()),
+                ("Y".to_string(), "1".to_string(), "list entry is not a selector string".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expand_version() {
+        let re = Regex::new(r"v(\d+)(-beta)?").unwrap();
+        let caps = re.captures("v3-beta").unwrap();
+        assert_eq!(patterns::expand_version(r"\1", &caps), Some("3".to_string()));
+        assert_eq!(patterns::expand_version(r"\2?\1b:\1", &caps), Some("3b".to_string()));
+        let caps = re.captures("v3").unwrap();
+        assert_eq!(patterns::expand_version(r"\2?\1b:\1", &caps), Some("3".to_string()));
+        assert_eq!(patterns::expand_version(r"\2", &caps), None);
+    }
+
+    #[test]
+    fn test_detect_http_per_hop() {
+        let d = detector();
+        let exchange = HttpExchange::new(
+            "http://example.com/".to_string(),
+            vec![
+                HttpHop::new(
+                    "http://example.com/".to_string(),
+                    301,
+                    vec![
+                        ("X-Powered-By".to_string(), " jQuery ".to_string()),
+                        ("Location".to_string(), "https://example.com/".to_string()),
+                    ],
+                ),
+                HttpHop::new(
+                    "https://example.com/".to_string(),
+                    200,
+                    vec![
+                        ("Link".to_string(), "<https://fonts.example>".to_string()),
+                        ("link".to_string(), "<https://a.shopifycloud.com/x.js>".to_string()),
+                        (
+                            "Set-Cookie".to_string(),
+                            "wordpress_test_cookie=WP+Cookie+check; path=/".to_string(),
+                        ),
+                    ],
+                ),
+            ],
+            None,
+        );
+        assert_eq!(exchange.status(), Some(200));
+        assert_eq!(
+            exchange.cookies(),
+            vec![("wordpress_test_cookie".to_string(), "WP+Cookie+check".to_string())]
+        );
+
+        let found: Vec<(String, String, usize, u16)> = d
+            .detect_http(vec![exchange])
+            .into_iter()
+            .map(|m| (m.name, m.field, m.hop, m.status))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("jQuery".to_string(), "headers".to_string(), 0, 301),
+                ("Shopify".to_string(), "headers".to_string(), 1, 200),
+                ("WordPress".to_string(), "cookies".to_string(), 1, 200),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_detections() {
+        let versions = |pairs: &[(&str, Option<&str>)]| -> HashMap<String, Option<String>> {
+            pairs.iter().map(|(n, v)| (n.to_string(), v.map(str::to_string))).collect()
+        };
+        let previous = versions(&[
+            ("jQuery", Some("1.12.4")),
+            ("React", Some("17.0.2")),
+            ("Shopify", None),
+            ("WordPress", Some("6.1")),
+        ]);
+        let current = versions(&[
+            ("jQuery", Some("3.7.1")),
+            ("React", None),
+            ("Cloudflare", None),
+            ("WordPress", Some("5.9")),
+            ("Webpack", None),
+        ]);
+        let diff = diff::diff_detections(
+            previous,
+            current,
+            vec!["Webpack".to_string(), "Babel".to_string()],
+            vec!["Node.js".to_string()],
+        );
+        assert_eq!(diff.added, vec!["Cloudflare"]);
+        assert_eq!(diff.removed, vec!["Shopify"]);
+        // Webpack moved from implied to direct: still present, no change
+        assert_eq!(diff.implied_added, vec!["Node.js"]);
+        assert_eq!(diff.implied_removed, vec!["Babel"]);
+        let changes: Vec<(&str, &str, &str, Option<bool>)> = diff
+            .version_changed
+            .iter()
+            .map(|c| (c.name.as_str(), c.previous.as_str(), c.current.as_str(), c.upgrade))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![("WordPress", "6.1", "5.9", Some(false)), ("jQuery", "1.12.4", "3.7.1", Some(true))]
+        );
+    }
+
+    #[test]
+    fn test_compare_versions() {
+        use std::cmp::Ordering;
+        assert_eq!(diff::compare_versions("2.10", "2.9"), Some(Ordering::Greater));
+        assert_eq!(diff::compare_versions("v3", "3.0"), Some(Ordering::Equal));
+        assert_eq!(diff::compare_versions("1.0-beta", "1.0"), None);
+    }
+
+    #[test]
+    fn test_lint_technologies() {
+        let json = r#"{
+            "Good": {"html": "good-marker", "headers": {"server": "good"}, "cats": [1]},
+            "Bad": {
+                "html": ["foo(?!bar)", "x?", ".*\\;version:\\1", "(unclosed"],
+                "js": {"a": ""},
+                "cookies": {"sess(?=id)": "", "token": "(?<=x)y"},
+                "implies": ["Good", "Missing"],
+                "dom": "div:has(> p)",
+                "scriptSrc": "bad\\.js",
+                "colour": "red"
+            }
+        }"#;
+        let diagnostics = lint::lint_technologies(json.as_bytes()).unwrap();
+        assert!(diagnostics.iter().all(|d| d.technology == "Bad"));
+        let found: Vec<(&str, Option<&str>, &str)> = diagnostics
+            .iter()
+            .map(|d| (d.field.as_str(), d.pattern.as_deref(), d.code.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("colour", None, "unknown-key"),
+                ("cookies", Some("sess(?=id)"), "unsupported-syntax"),
+                ("cookies", Some("(?<=x)y"), "unsupported-syntax"),
+                ("dom", None, "untranslated-selector"),
+                ("html", Some("foo(?!bar)"), "unsupported-syntax"),
+                ("html", Some("x?"), "broad-pattern"),
+                ("html", Some(".*\\;version:\\1"), "dropped"),
+                ("html", Some("(unclosed"), "invalid-regex"),
+                ("implies", Some("Missing"), "dangling-implies"),
+                ("js", Some(""), "dropped"),
+                ("scriptSrc", None, "ignored-key"),
+            ]
+        );
+        assert_eq!(diagnostics[1].key.as_deref(), Some("sess(?=id)"));
+        assert_eq!(diagnostics[4].severity, "error");
+        assert_eq!(diagnostics[5].severity, "warning");
+    }
+
+    #[test]
+    fn test_is_key_pattern() {
+        assert!(patterns::is_key_pattern("wordpress_[a-z0-9_]+"));
+        assert!(!patterns::is_key_pattern("asp.net_sessionid"));
+        assert!(!patterns::is_key_pattern("x-powered-by"));
+    }
+
     #[test]
     fn test_cut_version_strips_suffix() {
         assert_eq!(
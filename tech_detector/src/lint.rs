@@ -0,0 +1,298 @@
+// Validation of fingerprint JSON for pattern authors: everything the detector
+// would silently skip, degrade or never look at, as structured diagnostics
+
+use std::collections::HashSet;
+
+use pyo3::prelude::*;
+use regex_automata::util::syntax;
+use serde_json::{Map, Value};
+
+use crate::{deps, dom, patterns};
+
+/// Patterns that can match fewer characters than this are reported as too broad.
+const MIN_MATCH_LEN: usize = 3;
+
+/// Fields the detector reads patterns from.
+const PATTERN_FIELDS: &[&str] = &[
+    "html", "script", "dom", "js", "headers", "cookies", "meta", "dns", "certIssuer",
+    "robots", "xhr",
+];
+/// Fields kept as metadata or used for dependency resolution.
+const OTHER_FIELDS: &[&str] = &[
+    "implies", "cats", "website", "icon", "description", "cpe", "pricing", "saas", "oss",
+];
+/// Valid in the upstream schema but not used by this detector.
+const IGNORED_FIELDS: &[&str] = &[
+    "scriptSrc", "scripts", "css", "text", "url", "probe", "excludes", "requires",
+    "requiresCategory",
+];
+
+/// One problem found in a technology definition.
+///
+/// `code` is one of `unknown-key`, `ignored-key`, `invalid-type`, `invalid-regex`,
+/// `unsupported-syntax`, `dropped`, `broad-pattern`, `untranslated-selector`,
+/// `dangling-implies`.
+#[pyclass(get_all, module = "tech_detector")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub technology: String,
+    pub field: String,
+    /// Dict key (`headers`, `js`, ...) or dom selector the pattern belongs to
+    pub key: Option<String>,
+    /// The offending pattern as written in the JSON, if any
+    pub pattern: Option<String>,
+    /// `error` when the pattern is never used, `warning` otherwise
+    pub severity: String,
+    pub code: String,
+    pub message: String,
+}
+
+#[pymethods]
+impl Diagnostic {
+    fn __repr__(&self) -> String {
+        let location = match &self.key {
+            Some(key) => format!("{}.{}[{key}]", self.technology, self.field),
+            None => format!("{}.{}", self.technology, self.field),
+        };
+        format!("<Diagnostic {} {location}: {}>", self.code, self.message)
+    }
+}
+
+struct Linter<'a> {
+    technology: &'a str,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Linter<'_> {
+    fn report(
+        &mut self,
+        field: &str,
+        key: Option<&str>,
+        pattern: Option<&str>,
+        code: &str,
+        message: String,
+    ) {
+        let severity = match code {
+            "invalid-type" | "invalid-regex" | "unsupported-syntax" | "dropped"
+            | "untranslated-selector" => "error",
+            _ => "warning",
+        };
+        self.diagnostics.push(Diagnostic {
+            technology: self.technology.to_string(),
+            field: field.to_string(),
+            key: key.map(str::to_string),
+            pattern: pattern.map(str::to_string),
+            severity: severity.to_string(),
+            code: code.to_string(),
+            message,
+        });
+    }
+
+    /// Check a normalised pattern compiles and isn't too broad.
+    fn check_regex(&mut self, field: &str, key: Option<&str>, raw: &str, pattern: &str) {
+        match syntax::parse(pattern) {
+            Err(e) => {
+                self.report(field, key, Some(raw), "invalid-regex", regex_error(&e));
+            }
+            Ok(hir) => match hir.properties().minimum_len() {
+                Some(0) => {
+                    let message = "matches the empty string".to_string();
+                    self.report(field, key, Some(raw), "broad-pattern", message);
+                }
+                Some(n) if n < MIN_MATCH_LEN => {
+                    let message = format!("can match as few as {n} characters");
+                    self.report(field, key, Some(raw), "broad-pattern", message);
+                }
+                _ => {}
+            },
+        }
+    }
+
+    /// Why `cut_version` dropped a pattern.
+    fn report_dropped(&mut self, field: &str, key: Option<&str>, raw: &str) {
+        let message = if raw.contains("confidence:0") {
+            "confidence:0 patterns are ignored"
+        } else {
+            "catch-all expression with a version capture is ignored"
+        };
+        self.report(field, key, Some(raw), "dropped", message.to_string());
+    }
+
+    fn report_unsupported(&mut self, field: &str, key: Option<&str>, raw: &str, fallback: &str) {
+        let message = format!("lookaround or backreference; {fallback}");
+        self.report(field, key, Some(raw), "unsupported-syntax", message);
+    }
+
+    fn lint_list(&mut self, field: &str, value: &Value) {
+        let Some(raws) = strings(value) else {
+            let message = "expected a string or a list of strings".to_string();
+            return self.report(field, None, None, "invalid-type", message);
+        };
+        for raw in raws {
+            let Some(cut) = patterns::cut_version(raw) else {
+                self.report_dropped(field, None, raw);
+                continue;
+            };
+            if patterns::is_unsupported(&cut) {
+                self.report_unsupported(field, None, raw, "pattern skipped");
+                continue;
+            }
+            if let Some(pattern) = patterns::preprocess_list_pattern(raw) {
+                self.check_regex(field, None, raw, &pattern);
+            }
+        }
+    }
+
+    fn lint_js(&mut self, value: &Value) {
+        let Some(obj) = value.as_object() else {
+            let message = "expected an object of property -> pattern".to_string();
+            return self.report("js", None, None, "invalid-type", message);
+        };
+        for (var_name, val) in obj {
+            let raw = val.as_str().unwrap_or("");
+            let Some(cut) = patterns::cut_version(raw) else {
+                self.report_dropped("js", Some(var_name), raw);
+                continue;
+            };
+            if patterns::is_unsupported(&cut) {
+                self.report_unsupported("js", Some(var_name), raw, "pattern skipped");
+                continue;
+            }
+            match patterns::preprocess_merge_dict_pattern(var_name, raw) {
+                Some(pattern) => self.check_regex("js", Some(var_name), raw, &pattern),
+                None => {
+                    let message = "property name too short or reserved".to_string();
+                    self.report("js", Some(var_name), Some(raw), "dropped", message);
+                }
+            }
+        }
+    }
+
+    fn lint_dict(&mut self, field: &str, value: &Value) {
+        let Some(obj) = value.as_object() else {
+            let message = "expected an object of key -> pattern".to_string();
+            return self.report(field, None, None, "invalid-type", message);
+        };
+        for (key, val) in obj {
+            if patterns::is_key_pattern(key) {
+                if patterns::is_unsupported(key) {
+                    self.report_unsupported(field, Some(key), key, "key skipped");
+                } else if let Err(e) = syntax::parse(&format!("(?i)^(?:{key})$")) {
+                    self.report(field, Some(key), Some(key), "invalid-regex", regex_error(&e));
+                }
+            }
+            let Some(raws) = strings(val) else {
+                let message = "expected a string or a list of strings".to_string();
+                self.report(field, Some(key), None, "invalid-type", message);
+                continue;
+            };
+            for raw in raws.into_iter().filter(|raw| !raw.is_empty()) {
+                match patterns::preprocess_dict_value(raw) {
+                    Some(pattern) => {
+                        if let Err(e) = syntax::parse(&pattern) {
+                            let message = regex_error(&e);
+                            self.report(field, Some(key), Some(raw), "invalid-regex", message);
+                        }
+                    }
+                    // A catch-all value is a presence check, not a problem.
+                    None => match patterns::cut_version(raw) {
+                        None => self.report_dropped(field, Some(key), raw),
+                        Some(cut) if patterns::is_unsupported(&cut) => {
+                            let fallback = "only the key's presence is checked";
+                            self.report_unsupported(field, Some(key), raw, fallback);
+                        }
+                        Some(_) => {}
+                    },
+                }
+            }
+        }
+    }
+
+    fn lint_dom(&mut self, value: &Value) {
+        if !matches!(value, Value::String(_) | Value::Array(_) | Value::Object(_)) {
+            let message = "expected a selector, a list of selectors or an object".to_string();
+            return self.report("dom", None, None, "invalid-type", message);
+        }
+        for u in dom::dom_to_regex(value).untranslated {
+            self.report("dom", Some(&u.selector), None, "untranslated-selector", u.reason);
+        }
+    }
+
+    fn lint_implies(&mut self, value: &Value, known: &HashSet<&str>) {
+        let Some(raws) = strings(value) else {
+            let message = "expected a string or a list of strings".to_string();
+            return self.report("implies", None, None, "invalid-type", message);
+        };
+        for raw in raws {
+            let (name, _) = deps::parse_implies(raw);
+            if !known.contains(name) {
+                let message = format!("implies unknown technology {name:?}");
+                self.report("implies", None, Some(raw), "dangling-implies", message);
+            }
+        }
+    }
+}
+
+/// The last line of a parse error, which holds the reason without the pattern echo.
+fn regex_error(e: &impl std::fmt::Display) -> String {
+    e.to_string().lines().last().unwrap_or_default().trim_start_matches("error: ").to_string()
+}
+
+/// `Some` for a string or a list of strings, `None` for anything else.
+fn strings(value: &Value) -> Option<Vec<&str>> {
+    match value {
+        Value::String(s) => Some(vec![s.as_str()]),
+        Value::Array(arr) => arr.iter().map(Value::as_str).collect(),
+        _ => None,
+    }
+}
+
+/// Diagnostics for every technology in `apps`, ordered by technology then field.
+pub fn lint_apps(apps: &Map<String, Value>) -> Vec<Diagnostic> {
+    let known: HashSet<&str> = apps.keys().map(String::as_str).collect();
+    let mut names: Vec<&String> = apps.keys().collect();
+    names.sort();
+
+    let mut diagnostics = Vec::new();
+    for name in names {
+        let mut linter = Linter { technology: name, diagnostics: Vec::new() };
+        let Some(obj) = apps[name].as_object() else {
+            let message = "technology definition is not an object".to_string();
+            linter.report("", None, None, "invalid-type", message);
+            diagnostics.append(&mut linter.diagnostics);
+            continue;
+        };
+
+        let mut fields: Vec<(&String, &Value)> = obj.iter().collect();
+        fields.sort_by_key(|(field, _)| field.as_str());
+        for (field, value) in fields {
+            match field.as_str() {
+                "js" => linter.lint_js(value),
+                "dom" => linter.lint_dom(value),
+                "headers" | "cookies" | "meta" | "dns" => linter.lint_dict(field, value),
+                "implies" => linter.lint_implies(value, &known),
+                f if PATTERN_FIELDS.contains(&f) => linter.lint_list(f, value),
+                f if OTHER_FIELDS.contains(&f) => {}
+                f if IGNORED_FIELDS.contains(&f) => {
+                    let message = "not used by this detector".to_string();
+                    linter.report(f, None, None, "ignored-key", message);
+                }
+                f => {
+                    let message = format!("unknown field {f:?}");
+                    linter.report(f, None, None, "unknown-key", message);
+                }
+            }
+        }
+        diagnostics.append(&mut linter.diagnostics);
+    }
+    diagnostics
+}
+
+/// Validate a technologies JSON (same format as `TechDetector`) without building
+/// a detector.
+#[pyfunction]
+pub fn lint_technologies(json_data: &[u8]) -> PyResult<Vec<Diagnostic>> {
+    let root: Value = serde_json::from_slice(json_data)
+        .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+    Ok(lint_apps(crate::apps_object(&root)?))
+}
@@ -1,6 +1,9 @@
 // Pattern normalization — mirrors the Python _cut_version / _preprocess_* functions
 // in oceanai/mappings/common/technologies.py
 
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+
 const EMPTY_EXPR: &[&str] = &[
     ".*",
     "(.*)",
@@ -59,10 +62,9 @@ pub fn preprocess_list_pattern(text: &str) -> Option<String> {
     if is_unsupported(&text) {
         return None;
     }
-    let text = if text.starts_with('^') {
-        text[1..].to_string()
-    } else {
-        text
+    let text = match text.strip_prefix('^') {
+        Some(rest) => rest.to_string(),
+        None => text,
     };
     Some(replace_js_regexp(&text))
 }
@@ -76,10 +78,9 @@ pub fn preprocess_merge_dict_pattern(var_name: &str, value: &str) -> Option<Stri
     }
     let escaped = regex::escape(var_name);
     let suffix = cut_version(value)?;
-    let suffix = if suffix.starts_with('^') {
-        suffix[1..].to_string()
-    } else {
-        suffix
+    let suffix = match suffix.strip_prefix('^') {
+        Some(rest) => rest.to_string(),
+        None => suffix,
     };
     let combined = format!("{escaped}{suffix}");
     let combined = replace_js_regexp(&combined);
@@ -93,6 +94,13 @@ pub fn preprocess_merge_dict_pattern(var_name: &str, value: &str) -> Option<Stri
     Some(combined)
 }
 
+/// Whether a dict key (`headers`, `cookies`, `meta`) is a regex rather than a
+/// literal name. `.` alone doesn't count: it appears in plain cookie names like
+/// `ASP.NET_SessionId` and still matches itself.
+pub fn is_key_pattern(key: &str) -> bool {
+    key.contains(['[', ']', '(', ')', '{', '}', '*', '+', '?', '|', '^', '$', '\\'])
+}
+
 /// Normalise a value pattern from a dict field (`headers`, `cookies`, `meta`).
 /// Returns None if the key should match by presence only (empty/wildcard pattern).
 pub fn preprocess_dict_value(value: &str) -> Option<String> {
@@ -105,3 +113,70 @@ pub fn preprocess_dict_value(value: &str) -> Option<String> {
     }
     Some(replace_js_regexp(&text))
 }
+
+/// The `\;version:...` template of a pattern, e.g. `\1` or `\1?4:3`.
+pub fn version_template(text: &str) -> Option<String> {
+    text.split("\\;")
+        .skip(1)
+        .find_map(|tag| tag.strip_prefix("version:"))
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+}
+
+/// `(pattern, template)` for extracting a version from a dict value, whose
+/// pattern may be one of the presence-only expressions like `^(.+)$`.
+pub fn dict_version(value: &str) -> Option<(String, String)> {
+    let template = version_template(value)?;
+    let pattern = value.split("\\;").next().unwrap_or("");
+    if is_unsupported(pattern) {
+        return None;
+    }
+    Some((replace_js_regexp(pattern), template))
+}
+
+static TERNARY_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\\(\d+)\?([^:]*):(.*)$").unwrap());
+static BACKREF_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\\(\d+)").unwrap());
+
+/// Fill a version template from regex captures. Supports `\N` references and
+/// the `\N?then:else` ternary form. Returns None if the result is empty.
+pub fn expand_version(template: &str, caps: &Captures) -> Option<String> {
+    let group = |n: &str| {
+        n.parse::<usize>()
+            .ok()
+            .and_then(|i| caps.get(i))
+            .map_or("", |m| m.as_str())
+    };
+
+    let version = match TERNARY_RE.captures(template) {
+        Some(t) => {
+            let branch = if group(&t[1]).is_empty() { &t[3] } else { &t[2] };
+            BACKREF_RE.replace_all(branch, |c: &Captures| group(&c[1]).to_string()).into_owned()
+        }
+        None => BACKREF_RE
+            .replace_all(template, |c: &Captures| group(&c[1]).to_string())
+            .into_owned(),
+    };
+    let version = version.trim();
+    (!version.is_empty()).then(|| version.to_string())
+}
+
+/// A normalised pattern for a text matcher. The alternatives are OR-ed for
+/// detection and tried one at a time, in order, when extracting `version`.
+pub struct TextPattern {
+    pub alternatives: Vec<String>,
+    pub version: Option<String>,
+}
+
+impl TextPattern {
+    /// Normalise a list-field pattern, keeping its version template.
+    pub fn from_list(raw: &str) -> Option<Self> {
+        let pattern = preprocess_list_pattern(raw)?;
+        Some(TextPattern { alternatives: vec![pattern], version: version_template(raw) })
+    }
+
+    /// The single regex handed to the DFA.
+    pub fn combined(&self) -> String {
+        self.alternatives.join("|")
+    }
+}
@@ -0,0 +1,158 @@
+// Structured results returned to Python alongside the plain name lists
+
+use std::collections::HashMap;
+
+use pyo3::prelude::*;
+
+use crate::diff::{self, DetectionDiff};
+
+/// One detected technology.
+#[pyclass(get_all, module = "tech_detector")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Detection {
+    pub name: String,
+    /// From the first `\;version:` pattern that matched, if any
+    pub version: Option<String>,
+    /// Fields that matched (`html`, `headers`, ...); empty for implied technologies
+    pub sources: Vec<String>,
+    /// Added by dependency resolution rather than matched directly
+    pub implied: bool,
+    /// Shortest implies path from a directly detected technology to this one;
+    /// empty unless `implied`
+    pub implication_chain: Vec<String>,
+}
+
+#[pymethods]
+impl Detection {
+    fn __repr__(&self) -> String {
+        match &self.version {
+            Some(version) => format!("<Detection {} {version}>", self.name),
+            None => format!("<Detection {}>", self.name),
+        }
+    }
+}
+
+/// Every technology found by one `detect` call, sorted by name.
+#[pyclass(get_all, module = "tech_detector")]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DetectionReport {
+    pub detections: Vec<Detection>,
+}
+
+#[pymethods]
+impl DetectionReport {
+    pub fn names(&self) -> Vec<String> {
+        self.detections.iter().map(|d| d.name.clone()).collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<Detection> {
+        self.detections.iter().find(|d| d.name == name).cloned()
+    }
+
+    /// Technologies matched directly.
+    pub fn direct(&self) -> Vec<Detection> {
+        self.detections.iter().filter(|d| !d.implied).cloned().collect()
+    }
+
+    /// Technologies only present through `implies`.
+    pub fn implied(&self) -> Vec<Detection> {
+        self.detections.iter().filter(|d| d.implied).cloned().collect()
+    }
+
+    /// Directly detected name → version, the input `diff_detections` expects.
+    pub fn versions(&self) -> HashMap<String, Option<String>> {
+        self.detections
+            .iter()
+            .filter(|d| !d.implied)
+            .map(|d| (d.name.clone(), d.version.clone()))
+            .collect()
+    }
+
+    /// Changes from an earlier crawl of the same site to this one.
+    pub fn diff(&self, previous: &DetectionReport) -> DetectionDiff {
+        let implied_names = |report: &DetectionReport| -> Vec<String> {
+            report.implied().into_iter().map(|d| d.name).collect()
+        };
+        diff::diff_detections(
+            previous.versions(),
+            self.versions(),
+            implied_names(previous),
+            implied_names(self),
+        )
+    }
+
+    fn __len__(&self) -> usize {
+        self.detections.len()
+    }
+
+    fn __contains__(&self, name: &str) -> bool {
+        self.detections.iter().any(|d| d.name == name)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("<DetectionReport {} technologies>", self.detections.len())
+    }
+}
+
+/// Number of compiled patterns per field, plus what didn't make it in.
+#[pyclass(get_all, module = "tech_detector")]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PatternStats {
+    pub html: usize,
+    pub script: usize,
+    pub dom: usize,
+    pub js: usize,
+    pub headers: usize,
+    pub cookies: usize,
+    pub meta: usize,
+    pub dns: usize,
+    pub cert_issuer: usize,
+    pub robots: usize,
+    pub xhr: usize,
+    /// Technologies with at least one `implies` edge
+    pub implies: usize,
+    pub technologies: usize,
+    /// Patterns that failed to compile; see `skipped_patterns`
+    pub skipped: usize,
+    /// Dom selectors without a pattern; see `untranslated_selectors`
+    pub untranslated: usize,
+}
+
+#[pymethods]
+impl PatternStats {
+    /// Compiled patterns across all fields.
+    pub fn total(&self) -> usize {
+        self.html
+            + self.script
+            + self.dom
+            + self.js
+            + self.headers
+            + self.cookies
+            + self.meta
+            + self.dns
+            + self.cert_issuer
+            + self.robots
+            + self.xhr
+    }
+
+    /// Field name (as spelled in the JSON) → count, the `pattern_counts` format.
+    pub fn as_dict(&self) -> HashMap<String, usize> {
+        [
+            ("html", self.html),
+            ("script", self.script),
+            ("dom", self.dom),
+            ("js", self.js),
+            ("headers", self.headers),
+            ("cookies", self.cookies),
+            ("meta", self.meta),
+            ("dns", self.dns),
+            ("certIssuer", self.cert_issuer),
+            ("robots", self.robots),
+            ("xhr", self.xhr),
+            ("implies", self.implies),
+        ]
+        .into_iter()
+        .map(|(field, count)| (field.to_string(), count))
+        .collect()
+    }
+}
@@ -0,0 +1 @@
+from .tech_detector import *
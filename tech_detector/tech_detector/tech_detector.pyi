@@ -0,0 +1,207 @@
+from typing import Literal, Sequence, TypedDict, final
+
+DictValues = str | list[str]
+"""A header, cookie, meta or DNS value: one string or every value seen for the key."""
+
+TextKey = Literal["html", "script", "dom", "js", "certIssuer", "robots", "xhr"]
+DictKey = Literal["headers", "cookies", "meta", "dns"]
+
+class Technology(TypedDict, total=False):
+    """One entry of the technologies JSON passed to `TechDetector` as bytes."""
+
+    html: str | list[str]
+    script: str | list[str]
+    dom: str | list[str] | dict[str, dict[str, object]]
+    js: dict[str, str]
+    headers: dict[str, str | list[str]]
+    cookies: dict[str, str | list[str]]
+    meta: dict[str, str | list[str]]
+    dns: dict[str, str | list[str]]
+    certIssuer: str | list[str]
+    robots: str | list[str]
+    xhr: str | list[str]
+    implies: str | list[str]
+    cats: list[int]
+    website: str
+    icon: str
+    description: str
+    cpe: str
+    pricing: list[str]
+    saas: bool
+    oss: bool
+
+class Technologies(TypedDict):
+    apps: dict[str, Technology]
+
+@final
+class TechInfo:
+    name: str
+    website: str | None
+    icon: str | None
+    description: str | None
+    cpe: str | None
+    pricing: list[str]
+    saas: bool | None
+    oss: bool | None
+    cats: list[int]
+
+@final
+class HttpHop:
+    url: str
+    status: int
+    headers: list[tuple[str, str]]
+    def __init__(self, url: str, status: int, headers: list[tuple[str, str]] = ...) -> None: ...
+    def cookies(self) -> list[tuple[str, str]]: ...
+
+@final
+class HttpExchange:
+    request_url: str
+    final_url: str
+    hops: list[HttpHop]
+    def __init__(
+        self, request_url: str, hops: list[HttpHop], final_url: str | None = None
+    ) -> None: ...
+    def status(self) -> int | None: ...
+    def cookies(self) -> list[tuple[str, str]]: ...
+
+@final
+class HopMatch:
+    name: str
+    field: Literal["headers", "cookies"]
+    exchange: int
+    hop: int
+    url: str
+    status: int
+
+@final
+class Detection:
+    name: str
+    version: str | None
+    sources: list[str]
+    implied: bool
+    implication_chain: list[str]
+
+@final
+class DetectionReport:
+    detections: list[Detection]
+    def names(self) -> list[str]: ...
+    def get(self, name: str) -> Detection | None: ...
+    def direct(self) -> list[Detection]: ...
+    def implied(self) -> list[Detection]: ...
+    def versions(self) -> dict[str, str | None]: ...
+    def diff(self, previous: DetectionReport) -> DetectionDiff: ...
+    def __len__(self) -> int: ...
+    def __contains__(self, name: str) -> bool: ...
+
+@final
+class PatternStats:
+    html: int
+    script: int
+    dom: int
+    js: int
+    headers: int
+    cookies: int
+    meta: int
+    dns: int
+    cert_issuer: int
+    robots: int
+    xhr: int
+    implies: int
+    technologies: int
+    skipped: int
+    untranslated: int
+    def total(self) -> int: ...
+    def as_dict(self) -> dict[str, int]: ...
+
+@final
+class VersionChange:
+    name: str
+    previous: str
+    current: str
+    upgrade: bool | None
+
+@final
+class DetectionDiff:
+    added: list[str]
+    removed: list[str]
+    version_changed: list[VersionChange]
+    implied_added: list[str]
+    implied_removed: list[str]
+
+@final
+class Diagnostic:
+    technology: str
+    field: str
+    key: str | None
+    pattern: str | None
+    severity: Literal["error", "warning"]
+    code: Literal[
+        "unknown-key",
+        "ignored-key",
+        "invalid-type",
+        "invalid-regex",
+        "unsupported-syntax",
+        "dropped",
+        "broad-pattern",
+        "untranslated-selector",
+        "dangling-implies",
+    ]
+    message: str
+
+@final
+class TechDetector:
+    def __init__(self, json_data: bytes, chunk_size: int = 28) -> None:
+        """`json_data` is a UTF-8 encoded `Technologies` (or its `apps` mapping)."""
+    def detect_full(
+        self,
+        html_parts: Sequence[bytes],
+        script_parts: Sequence[bytes],
+        headers: Sequence[dict[str, DictValues]],
+        cookies: Sequence[dict[str, DictValues]],
+        meta_tags: Sequence[dict[str, DictValues]],
+        include_dependencies: bool = True,
+        *,
+        dns: dict[str, DictValues] | None = None,
+        cert_issuers: Sequence[str] | None = None,
+        robots: Sequence[str] | None = None,
+        xhr: Sequence[str] | None = None,
+        exchanges: Sequence[HttpExchange] | None = None,
+    ) -> list[str]: ...
+    def detect(
+        self,
+        html_parts: Sequence[bytes],
+        script_parts: Sequence[bytes],
+        headers: Sequence[dict[str, DictValues]],
+        cookies: Sequence[dict[str, DictValues]],
+        meta_tags: Sequence[dict[str, DictValues]],
+        include_dependencies: bool = True,
+        *,
+        dns: dict[str, DictValues] | None = None,
+        cert_issuers: Sequence[str] | None = None,
+        robots: Sequence[str] | None = None,
+        xhr: Sequence[str] | None = None,
+        exchanges: Sequence[HttpExchange] | None = None,
+    ) -> DetectionReport: ...
+    def detect_http(self, exchanges: Sequence[HttpExchange]) -> list[HopMatch]: ...
+    def resolve_dependencies(self, names: Sequence[str]) -> list[str]: ...
+    def implies_edges(self, name: str) -> list[tuple[str, int]]: ...
+    def dangling_implies(self) -> list[tuple[str, str]]: ...
+    def implies_cycles(self) -> list[list[str]]: ...
+    def implication_chain(self, detected: Sequence[str], name: str) -> list[str] | None: ...
+    def detect_text_key(self, key: TextKey, parts: Sequence[bytes]) -> list[str]: ...
+    def detect_versions(self, key: TextKey, parts: Sequence[bytes]) -> dict[str, str]: ...
+    def detect_dict_key(self, key: DictKey, data: dict[str, DictValues]) -> list[str]: ...
+    def technology_info(self, name: str) -> TechInfo | None: ...
+    def technologies_info(self, names: Sequence[str]) -> dict[str, TechInfo]: ...
+    def pattern_counts(self) -> dict[str, int]: ...
+    def pattern_stats(self) -> PatternStats: ...
+    def skipped_patterns(self) -> list[str]: ...
+    def untranslated_selectors(self) -> list[tuple[str, str, str]]: ...
+
+def diff_detections(
+    previous: dict[str, str | None],
+    current: dict[str, str | None],
+    previous_implied: Sequence[str] = ...,
+    current_implied: Sequence[str] = ...,
+) -> DetectionDiff: ...
+def lint_technologies(json_data: bytes) -> list[Diagnostic]: ...